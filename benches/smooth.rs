@@ -0,0 +1,31 @@
+//! Times `PrecomputedFilter::smooth` on a large input. Compare the scalar and
+//! vectorized interior loops with
+//!
+//! ```text
+//! cargo bench --bench smooth
+//! cargo bench --bench smooth --features simd
+//! ```
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use savgol::Filter;
+
+fn main() {
+    let data: Vec<f64> = (0..1_000_000).map(|x| (x as f64 * 0.001).sin()).collect();
+    for radius in [2, 5, 12] {
+        let precomputed = Filter::new(radius, 3, 0).precompute();
+        black_box(precomputed.smooth(black_box(&data)));
+        let runs = 20;
+        let start = Instant::now();
+        for _ in 0..runs {
+            black_box(precomputed.smooth(black_box(&data)));
+        }
+        let per_run = start.elapsed() / runs;
+        println!(
+            "radius {:>2}: {:>8.2} ms per 1M points",
+            radius,
+            per_run.as_secs_f64() * 1e3
+        );
+    }
+}
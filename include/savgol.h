@@ -0,0 +1,66 @@
+#ifndef SAVGOL_H
+#define SAVGOL_H
+
+/* Generated by cbindgen from src/ffi.rs; do not edit. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * A Savitzky–Golay filter of a given radius, polynomial degree, and
+ * derivative order.
+ *
+ * The window normally extends `radius` points either side of the point
+ * being smoothed, but `new_asymmetric` allows different extents before
+ * (`radius_left`) and after (`radius_right`) it. Filters from `new_window`
+ * with an even `window_length` instead evaluate each fit half a sample
+ * before the point, at the center of the window.
+ *
+ * A `Filter` is just its parameters: weights are computed when it is
+ * applied. To smooth many inputs without recomputing them, build a
+ * `PrecomputedFilter` with `precompute`. Neither type caches anything
+ * behind a shared reference, so both are `Send + Sync` and one filter can
+ * be shared across threads.
+ *
+ * Filters from `FilterBuilder` may also carry a sample spacing `delta`, which
+ * scales derivatives as in `smooth_scaled`, and an `edge_mode`, which
+ * `smooth`, `smooth_into`, `smooth_streaming_cb`, and `PrecomputedFilter` use
+ * in place of `EdgeMode::Interp`, and a `FallbackPolicy` for inputs shorter
+ * than the window. `delta` is validated to be positive and finite and
+ * compared by bit pattern, as are `EdgeMode::Constant` values, so `Filter`
+ * is `Eq` and `Hash` and can key a cache.
+ */
+typedef struct SavgolFilter SavgolFilter;
+
+/**
+ * Creates a filter as `Filter::new(radius, degree, deriv)` does, or returns
+ * NULL if the parameters are invalid. Release it with `savgol_filter_free`.
+ */
+SavgolFilter *savgol_filter_new(size_t radius, uint64_t degree, uint64_t deriv);
+
+/**
+ * Smooths the `len` values at `input` into the `len` values at `out`.
+ * Returns 0 on success and -1 if any pointer is NULL.
+ *
+ * # Safety
+ *
+ * `filter` must come from `savgol_filter_new` and not have been freed.
+ * `input` must be valid for reading and `out` for writing `len` doubles,
+ * and the two buffers must not overlap.
+ */
+int32_t savgol_filter_smooth(const SavgolFilter *filter,
+                             const double *input,
+                             size_t len,
+                             double *out);
+
+/**
+ * Releases a filter from `savgol_filter_new`. Does nothing for NULL.
+ *
+ * # Safety
+ *
+ * `filter` must be NULL or come from `savgol_filter_new`, and must not be
+ * used again afterwards.
+ */
+void savgol_filter_free(SavgolFilter *filter);
+
+#endif  /* SAVGOL_H */
@@ -0,0 +1,63 @@
+"""Compares the bindings against scipy.signal. Run with `pytest python/tests`
+after `maturin develop --extras test`."""
+
+import numpy as np
+import pytest
+import scipy.signal
+
+import savgol
+
+PARAMETERS = [
+    # window_length, polyorder, deriv, delta
+    (5, 2, 0, 1.0),
+    (7, 3, 1, 0.5),
+    (9, 4, 2, 2.0),
+    (11, 2, 0, 1.0),
+    (5, 1, 3, 1.0),
+]
+
+MODES = ["interp", "mirror", "nearest", "constant", "wrap"]
+
+
+@pytest.fixture
+def x():
+    t = np.arange(40, dtype=float)
+    return np.sin(0.3 * t) + 0.01 * t**2
+
+
+@pytest.mark.parametrize("window_length, polyorder, deriv, delta", PARAMETERS)
+@pytest.mark.parametrize("mode", MODES)
+def test_savgol_filter_matches_scipy(x, window_length, polyorder, deriv, delta, mode):
+    expected = scipy.signal.savgol_filter(
+        x, window_length, polyorder, deriv=deriv, delta=delta, mode=mode, cval=1.5
+    )
+    actual = savgol.savgol_filter(
+        x, window_length, polyorder, deriv=deriv, delta=delta, mode=mode, cval=1.5
+    )
+    np.testing.assert_allclose(actual, expected, rtol=1e-9, atol=1e-9)
+
+
+@pytest.mark.parametrize("window_length, polyorder, deriv, delta", PARAMETERS)
+@pytest.mark.parametrize("use", ["conv", "dot"])
+def test_savgol_coeffs_matches_scipy(window_length, polyorder, deriv, delta, use):
+    expected = scipy.signal.savgol_coeffs(
+        window_length, polyorder, deriv=deriv, delta=delta, use=use
+    )
+    actual = savgol.savgol_coeffs(
+        window_length, polyorder, deriv=deriv, delta=delta, use=use
+    )
+    np.testing.assert_allclose(actual, expected, rtol=1e-9, atol=1e-12)
+
+
+def test_savgol_coeffs_pos_matches_scipy():
+    expected = scipy.signal.savgol_coeffs(7, 2, pos=1)
+    np.testing.assert_allclose(savgol.savgol_coeffs(7, 2, pos=1), expected, atol=1e-12)
+
+
+def test_invalid_arguments_raise_value_error(x):
+    with pytest.raises(ValueError):
+        savgol.savgol_filter(x, 5, 5)
+    with pytest.raises(ValueError):
+        savgol.savgol_filter(x[:3], 5, 2)
+    with pytest.raises(ValueError):
+        savgol.savgol_filter(x, 5, 2, mode="reflect")
@@ -0,0 +1,124 @@
+use ndarray::{Array1, Array2, ArrayD, Axis};
+
+use crate::edge::EdgeMode;
+use crate::filter::Filter;
+use crate::precomputed;
+
+impl Filter {
+    /// Smooths a one-dimensional `ndarray` array, as `smooth` does a slice.
+    pub fn smooth_array1(&self, a: &Array1<f64>) -> Array1<f64> {
+        match a.as_slice() {
+            Some(data) => Array1::from(self.smooth(data)),
+            None => Array1::from(self.smooth(&a.to_vec())),
+        }
+    }
+
+    /// Smooths every lane of `a` along `axis` independently, e.g. each
+    /// column of a 2D array for `Axis(0)`. The weights are computed once and
+    /// shared by all lanes.
+    pub fn smooth_axis(&self, a: &ArrayD<f64>, axis: Axis) -> ArrayD<f64> {
+        let precomputed = self.precompute();
+        let mut smoothed = a.clone();
+        let mut lane_data = Vec::with_capacity(a.len_of(axis));
+        for (mut output, lane) in smoothed.lanes_mut(axis).into_iter().zip(a.lanes(axis)) {
+            lane_data.clear();
+            lane_data.extend(lane.iter());
+            for (value, result) in output.iter_mut().zip(precomputed.smooth(&lane_data)) {
+                *value = result;
+            }
+        }
+        smoothed
+    }
+
+    /// Smooths every lane of the 2D array `a` along `axis`, e.g. each row for
+    /// `Axis(1)`, treating the ends of each lane as `smooth_with` does for
+    /// `mode`. The weights are computed once and shared by all lanes.
+    pub fn smooth_axis_with(&self, a: &Array2<f64>, axis: Axis, mode: EdgeMode) -> Array2<f64> {
+        if mode == EdgeMode::Interp || a.len_of(axis) == 0 {
+            let dynamic = a.clone().into_dyn();
+            let smoothed = self.smooth_axis(&dynamic, axis);
+            return smoothed.into_dimensionality().unwrap();
+        }
+        let kernel = self.coefficients();
+        let (left, right) = (self.radius_left() as isize, self.radius_right() as isize);
+        let mut smoothed = a.clone();
+        let mut lane_data: Vec<f64> = Vec::with_capacity(a.len_of(axis));
+        let mut padded = Vec::with_capacity(a.len_of(axis) + kernel.len());
+        for (mut output, lane) in smoothed.lanes_mut(axis).into_iter().zip(a.lanes(axis)) {
+            lane_data.clear();
+            lane_data.extend(lane.iter());
+            padded.clear();
+            padded.extend(
+                (-left..lane_data.len() as isize + right)
+                    .map(|index| mode.extend(&lane_data, index)),
+            );
+            for (value, window) in output.iter_mut().zip(padded.windows(kernel.len())) {
+                *value = precomputed::dot(&kernel, window);
+            }
+        }
+        smoothed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use ndarray::{Array1, Array2, Axis};
+
+    use crate::{EdgeMode, Filter};
+
+    fn assert_all_float_eq(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, b) in actual.iter().zip(expected) {
+            assert_relative_eq!(a, b, epsilon = 1e-10);
+        }
+    }
+
+    #[test]
+    fn smooth_array1_matches_smooth() {
+        let filter = Filter::new(2, 2, 0);
+        let data = vec![1.0, -2.0, 3.0, -4.0, 5.0, -6.0, 7.0];
+        let smoothed = filter.smooth_array1(&Array1::from(data.clone()));
+        assert_all_float_eq(smoothed.as_slice().unwrap(), &filter.smooth(&data));
+    }
+
+    #[test]
+    fn smooth_axis_0_and_1_of_2d_array() {
+        let filter = Filter::new(2, 2, 0);
+        let a = Array2::from_shape_fn((7, 6), |(i, j)| ((i * 6 + j) as f64 * 0.9).sin());
+        let dynamic = a.clone().into_dyn();
+
+        let by_column = filter.smooth_axis(&dynamic, Axis(0));
+        assert_eq!(by_column.shape(), &[7, 6]);
+        for j in 0..6 {
+            let column = a.column(j).to_vec();
+            let smoothed: Vec<f64> = by_column.index_axis(Axis(1), j).iter().copied().collect();
+            assert_all_float_eq(&smoothed, &filter.smooth(&column));
+        }
+
+        let by_row = filter.smooth_axis(&dynamic, Axis(1));
+        for i in 0..7 {
+            let row = a.row(i).to_vec();
+            let smoothed: Vec<f64> = by_row.index_axis(Axis(0), i).iter().copied().collect();
+            assert_all_float_eq(&smoothed, &filter.smooth(&row));
+        }
+    }
+
+    #[test]
+    fn smooth_axis_with_mirror_matches_smooth_with() {
+        let filter = Filter::new(3, 2, 0);
+        let a = Array2::from_shape_fn((4, 10), |(i, j)| ((i * 10 + j) as f64 * 0.7).cos());
+        let by_row = filter.smooth_axis_with(&a, Axis(1), EdgeMode::Mirror);
+        assert_eq!(by_row.dim(), (4, 10));
+        for i in 0..4 {
+            let row = a.row(i).to_vec();
+            assert_all_float_eq(
+                &by_row.row(i).to_vec(),
+                &filter.smooth_with(&row, EdgeMode::Mirror),
+            );
+        }
+        let by_column = filter.smooth_axis_with(&a, Axis(0), EdgeMode::Interp);
+        let column = a.column(2).to_vec();
+        assert_all_float_eq(&by_column.column(2).to_vec(), &filter.smooth(&column));
+    }
+}
@@ -0,0 +1,96 @@
+use alloc::vec::Vec;
+
+use crate::precomputed::dot;
+
+/// The `n x n` smoothing matrix of a `Filter`, stored by its band.
+///
+/// Every interior row holds the same kernel on its diagonal band, so it is
+/// stored once. The first and last rows use the edge weights
+/// and are stored as their nonzero leading (or trailing) entries only, which
+/// keeps storage at O(radius^2) regardless of `n`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BandedMatrix {
+    n: usize,
+    kernel: Vec<f64>,
+    head: Vec<Vec<f64>>,
+    tail: Vec<Vec<f64>>,
+}
+
+impl BandedMatrix {
+    /// `head` rows start at column 0 and `tail` rows end at column `n - 1`.
+    /// Interior row `head.len() + j` applies `kernel` starting at column `j`.
+    pub(crate) fn new(
+        n: usize,
+        kernel: Vec<f64>,
+        head: Vec<Vec<f64>>,
+        tail: Vec<Vec<f64>>,
+    ) -> Self {
+        BandedMatrix {
+            n,
+            kernel,
+            head,
+            tail,
+        }
+    }
+
+    /// A matrix too small to have a band, stored as dense rows.
+    pub(crate) fn dense(rows: Vec<Vec<f64>>) -> Self {
+        BandedMatrix::new(rows.len(), Vec::new(), rows, Vec::new())
+    }
+
+    /// Number of rows (and columns) of the matrix.
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// Number of `f64` values actually stored.
+    pub fn stored_len(&self) -> usize {
+        self.kernel.len()
+            + self
+                .head
+                .iter()
+                .chain(&self.tail)
+                .map(Vec::len)
+                .sum::<usize>()
+    }
+
+    /// The stored entries of `row` and the column of the first of them.
+    pub(crate) fn band(&self, row: usize) -> (&[f64], usize) {
+        assert!(row < self.n);
+        let tail_start = self.n - self.tail.len();
+        if row < self.head.len() {
+            (&self.head[row], 0)
+        } else if row >= tail_start {
+            let values = &self.tail[row - tail_start];
+            (values, self.n - values.len())
+        } else {
+            (&self.kernel, row - self.head.len())
+        }
+    }
+
+    /// Returns the entry at `(row, col)`, which is zero outside the band.
+    pub fn get(&self, row: usize, col: usize) -> f64 {
+        assert!(row < self.n && col < self.n);
+        let (values, start) = self.band(row);
+        match col.checked_sub(start) {
+            Some(offset) if offset < values.len() => values[offset],
+            _ => 0.0,
+        }
+    }
+
+    /// Multiplies the matrix by `x`.
+    pub fn apply(&self, x: &[f64]) -> Vec<f64> {
+        assert!(x.len() == self.n);
+        let mut y = Vec::with_capacity(self.n);
+        for row in &self.head {
+            y.push(dot(row, &x[..row.len()]));
+        }
+        for start in 0..self.n - self.head.len() - self.tail.len() {
+            y.push(dot(&self.kernel, &x[start..start + self.kernel.len()]));
+        }
+        for row in &self.tail {
+            y.push(dot(row, &x[self.n - row.len()..]));
+        }
+        y
+    }
+}
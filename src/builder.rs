@@ -0,0 +1,197 @@
+use crate::edge::EdgeMode;
+use crate::error::FilterError;
+use crate::fallback::FallbackPolicy;
+use crate::filter::Filter;
+
+/// Builds a `Filter` from named settings, for when the positional
+/// constructors get unwieldy.
+///
+/// The window must be set, with either `radius` or `window_length` (the last
+/// one set wins). Everything else has a default: degree 2, derivative 0,
+/// `delta` 1, `EdgeMode::Interp`, and `FallbackPolicy::Shrink`.
+///
+/// ```
+/// use savgol::{EdgeMode, FilterBuilder};
+///
+/// let filter = FilterBuilder::new()
+///     .window_length(7)
+///     .degree(3)
+///     .derivative(1)
+///     .delta(0.5)
+///     .edge_mode(EdgeMode::Mirror)
+///     .build()
+///     .unwrap();
+/// assert_eq!(filter.radius(), 3);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FilterBuilder {
+    /// The window set so far, or the error to report from `build`.
+    window_length: Result<usize, FilterError>,
+    degree: u64,
+    derivative: u64,
+    delta: f64,
+    edge_mode: EdgeMode,
+    fallback_policy: FallbackPolicy,
+}
+
+impl Default for FilterBuilder {
+    fn default() -> Self {
+        FilterBuilder {
+            window_length: Err(FilterError::ZeroRadius),
+            degree: 2,
+            derivative: 0,
+            delta: 1.0,
+            edge_mode: EdgeMode::Interp,
+            fallback_policy: FallbackPolicy::Shrink,
+        }
+    }
+}
+
+impl FilterBuilder {
+    pub fn new() -> Self {
+        FilterBuilder::default()
+    }
+
+    /// Uses a window of `2 * radius + 1` points. A radius whose window
+    /// length overflows makes `build` return `FilterError::RadiusTooLarge`.
+    pub fn radius(mut self, radius: usize) -> Self {
+        self.window_length = radius
+            .checked_mul(2)
+            .and_then(|span| span.checked_add(1))
+            .ok_or(FilterError::RadiusTooLarge);
+        self
+    }
+
+    /// Uses a window of `window_length` points, which may be even as in
+    /// `Filter::new_window`.
+    pub fn window_length(mut self, window_length: usize) -> Self {
+        self.window_length = Ok(window_length);
+        self
+    }
+
+    pub fn degree(mut self, degree: u64) -> Self {
+        self.degree = degree;
+        self
+    }
+
+    pub fn derivative(mut self, derivative: u64) -> Self {
+        self.derivative = derivative;
+        self
+    }
+
+    /// Spacing between samples, which derivatives are taken with respect to.
+    /// It must be positive and finite, or `build` returns
+    /// `FilterError::InvalidDelta`.
+    pub fn delta(mut self, delta: f64) -> Self {
+        self.delta = delta;
+        self
+    }
+
+    pub fn edge_mode(mut self, edge_mode: EdgeMode) -> Self {
+        self.edge_mode = edge_mode;
+        self
+    }
+
+    /// What to do with inputs shorter than the window.
+    pub fn fallback_policy(mut self, fallback_policy: FallbackPolicy) -> Self {
+        self.fallback_policy = fallback_policy;
+        self
+    }
+
+    /// Creates the filter, with the same checks as `Filter::try_new_window`.
+    /// A window that was never set is rejected as `FilterError::ZeroRadius`.
+    pub fn build(self) -> Result<Filter, FilterError> {
+        let window_length = self.window_length?;
+        if !(self.delta > 0.0 && self.delta.is_finite()) {
+            return Err(FilterError::InvalidDelta);
+        }
+        let filter = Filter::try_new_window(window_length, self.degree, self.derivative)?;
+        Ok(filter
+            .with_options(self.delta, self.edge_mode)
+            .with_fallback_policy(self.fallback_policy))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use approx::assert_relative_eq;
+
+    use super::FilterBuilder;
+    use crate::{EdgeMode, Filter, FilterError};
+
+    #[test]
+    fn radius_and_window_length_build_the_same_filter() {
+        let by_radius = FilterBuilder::new()
+            .radius(3)
+            .degree(2)
+            .derivative(1)
+            .build();
+        let by_length = FilterBuilder::new()
+            .window_length(7)
+            .degree(2)
+            .derivative(1)
+            .build();
+        assert_eq!(by_radius, by_length);
+        assert_eq!(by_radius, Ok(Filter::new(3, 2, 1)));
+    }
+
+    #[test]
+    fn build_requires_a_window() {
+        assert_eq!(FilterBuilder::new().build(), Err(FilterError::ZeroRadius));
+        assert_eq!(
+            FilterBuilder::new().radius(1).degree(3).build(),
+            Err(FilterError::DegreeExceedsWindow {
+                degree: 3,
+                window_length: 3
+            })
+        );
+    }
+
+    #[test]
+    fn build_rejects_invalid_delta() {
+        for delta in [0.0, -0.5, f64::NAN, f64::INFINITY] {
+            assert_eq!(
+                FilterBuilder::new().radius(2).delta(delta).build(),
+                Err(FilterError::InvalidDelta)
+            );
+        }
+    }
+
+    #[test]
+    fn delta_scales_derivatives() {
+        let data: Vec<f64> = (0..9).map(|x| 3.0 * x as f64).collect();
+        for len in [2, 9] {
+            let filter = FilterBuilder::new()
+                .radius(2)
+                .derivative(1)
+                .delta(0.5)
+                .build()
+                .unwrap();
+            for slope in filter.smooth(&data[..len]) {
+                assert_relative_eq!(slope, 6.0, epsilon = 1e-10);
+            }
+            for slope in filter.precompute().smooth(&data[..len]) {
+                assert_relative_eq!(slope, 6.0, epsilon = 1e-10);
+            }
+        }
+    }
+
+    #[test]
+    fn edge_mode_applies_to_smooth() {
+        let data = [1.0, -2.0, 3.0, -4.0, 5.0, -6.0, 7.0];
+        let filter = FilterBuilder::new()
+            .radius(2)
+            .edge_mode(EdgeMode::Nearest)
+            .build()
+            .unwrap();
+        let expected = Filter::new(2, 2, 0).smooth_with(&data, EdgeMode::Nearest);
+        assert_eq!(filter.smooth(&data), expected);
+        assert_eq!(filter.precompute().smooth(&data), expected);
+        assert_eq!(
+            filter.smooth_with(&data, EdgeMode::Interp),
+            Filter::new(2, 2, 0).smooth(&data)
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use num_traits::Float;
+
+use crate::edge::EdgeMode;
+use crate::filter::Filter;
+use crate::precomputed::PrecomputedFilter;
+
+/// A `Filter` that remembers the weights it computes, for smoothing many
+/// inputs in a loop. Build one with `Filter::into_cached`.
+///
+/// The first input of each length computes a `PrecomputedFilter` for it;
+/// later inputs of that length reuse it without any weight computation.
+/// Every input at least as long as the window shares one entry, while each
+/// shorter length gets its own shrunken filter. Outputs match
+/// `Filter::smooth`. Filters with an edge mode other than `Interp` are not
+/// cached and call `Filter::smooth` every time.
+///
+/// `smooth` takes `&mut self` to fill the cache, so a `CachedFilter` stays
+/// `Send + Sync` but cannot be shared between threads that smooth
+/// concurrently; give each thread its own.
+pub struct CachedFilter {
+    filter: Filter,
+    /// Keyed by the window length used, `min(len, window_length)`.
+    cache: BTreeMap<usize, PrecomputedFilter>,
+}
+
+impl CachedFilter {
+    pub fn new(filter: Filter) -> Self {
+        CachedFilter {
+            filter,
+            cache: BTreeMap::new(),
+        }
+    }
+
+    /// The wrapped filter.
+    pub fn filter(&self) -> &Filter {
+        &self.filter
+    }
+
+    /// Smooths `data` like `Filter::smooth`, computing weights only the first
+    /// time its length is seen.
+    ///
+    /// # Panics
+    ///
+    /// If the filter's `FallbackPolicy` rejects `data` as too short.
+    pub fn smooth<T: Float>(&mut self, data: &[T]) -> Vec<T> {
+        if let Err(error) = self.filter.check_len(data.len()) {
+            panic!("{}", error);
+        }
+        // One or two points need no weights.
+        if data.len() <= 2 || self.filter.edge_mode() != EdgeMode::Interp {
+            return self.filter.smooth(data);
+        }
+        let window_length = self.filter.window_length();
+        let filter = &self.filter;
+        self.cache
+            .entry(data.len().min(window_length))
+            .or_insert_with(|| {
+                if data.len() < window_length {
+                    filter.shrunk(data.len()).precompute()
+                } else {
+                    filter.precompute()
+                }
+            })
+            .smooth(data)
+    }
+}
+
+// The table counter is thread-local, which needs std.
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use crate::math::TABLES_BUILT;
+    use crate::Filter;
+
+    #[test]
+    fn repeated_calls_match_smooth_without_recomputing() {
+        let filter = Filter::new(4, 3, 1);
+        let mut cached = filter.into_cached();
+        let data: Vec<f64> = (0..50).map(|x| (x as f64 * 0.2).sin()).collect();
+        let other: Vec<f64> = data.iter().map(|x| x * x).collect();
+        let expected = filter.smooth(&other);
+        let first = cached.smooth(&data);
+        assert_eq!(first, filter.smooth(&data));
+
+        let built = TABLES_BUILT.with(|built| built.get());
+        assert_eq!(cached.smooth(&data), first);
+        assert_eq!(cached.smooth(&other), expected);
+        assert_eq!(TABLES_BUILT.with(|built| built.get()), built);
+    }
+
+    #[test]
+    fn short_lengths_are_cached_separately() {
+        let filter = Filter::new(10, 2, 0);
+        let mut cached = filter.into_cached();
+        let data = [1.0, 4.0, 2.0, 8.0, 5.0];
+        assert_eq!(cached.smooth(&data), filter.smooth(&data));
+        assert_eq!(cached.smooth(&data[..3]), filter.smooth(&data[..3]));
+        let built = TABLES_BUILT.with(|built| built.get());
+        assert_eq!(cached.smooth(&data), filter.smooth(&data));
+        assert_eq!(cached.smooth(&data[..3]), filter.smooth(&data[..3]));
+        assert_eq!(cached.smooth(&data[..2]), filter.smooth(&data[..2]));
+        // Only the direct `filter.smooth` calls built tables.
+        assert_eq!(TABLES_BUILT.with(|built| built.get()), built + 2);
+    }
+}
@@ -0,0 +1,68 @@
+use alloc::vec::Vec;
+
+use num_complex::Complex;
+
+use crate::filter::Filter;
+
+impl Filter {
+    /// Smooths a complex signal. The weights are real, so this is the same
+    /// as smoothing the real and imaginary parts separately.
+    pub fn smooth_complex(&self, data: &[Complex<f64>]) -> Vec<Complex<f64>> {
+        let precomputed = self.precompute();
+        let re: Vec<f64> = data.iter().map(|z| z.re).collect();
+        let im: Vec<f64> = data.iter().map(|z| z.im).collect();
+        precomputed
+            .smooth(&re)
+            .into_iter()
+            .zip(precomputed.smooth(&im))
+            .map(|(re, im)| Complex::new(re, im))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use num_complex::Complex;
+
+    use crate::Filter;
+
+    #[test]
+    fn smooth_complex_ramp_smooths_each_part() {
+        let data: Vec<Complex<f64>> = (0..9)
+            .map(|x| Complex::new(x as f64, -2.0 * x as f64 + 1.0))
+            .collect();
+
+        let smoothed = Filter::new(2, 2, 0).smooth_complex(&data);
+        for (z, expected) in smoothed.iter().zip(&data) {
+            assert_relative_eq!(z.re, expected.re, epsilon = 1e-10);
+            assert_relative_eq!(z.im, expected.im, epsilon = 1e-10);
+        }
+
+        let slope = Filter::new(2, 2, 1).smooth_complex(&data);
+        for z in slope {
+            assert_relative_eq!(z.re, 1.0, epsilon = 1e-10);
+            assert_relative_eq!(z.im, -2.0, epsilon = 1e-10);
+        }
+    }
+
+    #[test]
+    fn smooth_complex_matches_parts_smoothed_separately() {
+        let filter = Filter::new(3, 2, 0);
+        let data: Vec<Complex<f64>> = (0..12)
+            .map(|x| Complex::new((x as f64 * 0.7).sin(), (x as f64 * 1.3).cos()))
+            .collect();
+        let re: Vec<f64> = data.iter().map(|z| z.re).collect();
+        let im: Vec<f64> = data.iter().map(|z| z.im).collect();
+
+        let smoothed = filter.smooth_complex(&data);
+        for ((z, re), im) in smoothed
+            .iter()
+            .zip(filter.smooth(&re))
+            .zip(filter.smooth(&im))
+        {
+            assert_relative_eq!(z.re, re, epsilon = 1e-10);
+            assert_relative_eq!(z.im, im, epsilon = 1e-10);
+        }
+    }
+}
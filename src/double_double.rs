@@ -0,0 +1,126 @@
+//! "Double-double" arithmetic: a value is the unevaluated sum of two `f64`s,
+//! giving about 106 bits of significand. The algorithms are the standard
+//! ones of Dekker (1971) and the QD library (Hida, Li, and Bailey, 2001).
+
+use core::ops::{Add, Div, Mul, Neg, Sub};
+
+use num_traits::Float;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct DoubleDouble {
+    hi: f64,
+    lo: f64,
+}
+
+/// `a + b` and its rounding error, exactly.
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    let bb = s - a;
+    (s, (a - (s - bb)) + (b - bb))
+}
+
+/// `two_sum` for `|a| >= |b|`.
+fn quick_two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    (s, b - (s - a))
+}
+
+/// `a * b` and its rounding error, exactly.
+fn two_prod(a: f64, b: f64) -> (f64, f64) {
+    let p = a * b;
+    (p, Float::mul_add(a, b, -p))
+}
+
+impl DoubleDouble {
+    pub(crate) const ZERO: DoubleDouble = DoubleDouble { hi: 0.0, lo: 0.0 };
+
+    fn normalized(hi: f64, lo: f64) -> Self {
+        let (hi, lo) = quick_two_sum(hi, lo);
+        DoubleDouble { hi, lo }
+    }
+
+    /// Rounds to the nearest `f64`.
+    pub(crate) fn to_f64(self) -> f64 {
+        self.hi + self.lo
+    }
+}
+
+impl From<f64> for DoubleDouble {
+    fn from(value: f64) -> Self {
+        DoubleDouble { hi: value, lo: 0.0 }
+    }
+}
+
+impl Neg for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn neg(self) -> DoubleDouble {
+        DoubleDouble {
+            hi: -self.hi,
+            lo: -self.lo,
+        }
+    }
+}
+
+impl Add for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn add(self, other: DoubleDouble) -> DoubleDouble {
+        let (s, e) = two_sum(self.hi, other.hi);
+        let (t, f) = two_sum(self.lo, other.lo);
+        let (s, e) = quick_two_sum(s, e + t);
+        DoubleDouble::normalized(s, e + f)
+    }
+}
+
+impl Sub for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn sub(self, other: DoubleDouble) -> DoubleDouble {
+        self + -other
+    }
+}
+
+impl Mul for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn mul(self, other: DoubleDouble) -> DoubleDouble {
+        let (p, e) = two_prod(self.hi, other.hi);
+        DoubleDouble::normalized(p, e + (self.hi * other.lo + self.lo * other.hi))
+    }
+}
+
+impl Div for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn div(self, other: DoubleDouble) -> DoubleDouble {
+        // Long division, one f64 quotient digit at a time.
+        let q1 = self.hi / other.hi;
+        let r = self - other * q1.into();
+        let q2 = r.hi / other.hi;
+        let r = r - other * q2.into();
+        let q3 = r.hi / other.hi;
+        let (hi, lo) = quick_two_sum(q1, q2);
+        DoubleDouble { hi, lo } + q3.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DoubleDouble;
+
+    #[test]
+    fn keeps_bits_f64_rounds_away() {
+        let one = DoubleDouble::from(1.0);
+        let tiny = DoubleDouble::from(1e-20);
+        assert_eq!(((one + tiny) - one).to_f64(), 1e-20);
+        assert_eq!((1.0 + 1e-20) - 1.0, 0.0);
+    }
+
+    #[test]
+    fn division_is_exact_to_double_double_precision() {
+        let third = DoubleDouble::from(1.0) / DoubleDouble::from(3.0);
+        let residual = (third * DoubleDouble::from(3.0) - DoubleDouble::from(1.0)).to_f64();
+        assert!(residual.abs() < 1e-31);
+    }
+}
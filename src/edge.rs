@@ -0,0 +1,129 @@
+use core::hash::{Hash, Hasher};
+
+use num_traits::Float;
+
+/// How `Filter::smooth_with` treats the points within `radius` of each end.
+///
+/// The names follow `scipy.signal.savgol_filter`. Every mode except `Interp`
+/// extends the signal past its ends and applies the centered kernel
+/// everywhere. Shown for the right edge of `[1, 2, 3, 4]` with radius 2:
+///
+/// | mode          | padding            |
+/// |---------------|--------------------|
+/// | `Mirror`      | `1 2 3 4 \| 3 2`   |
+/// | `Nearest`     | `1 2 3 4 \| 4 4`   |
+/// | `Constant(c)` | `1 2 3 4 \| c c`   |
+/// | `Wrap`        | `1 2 3 4 \| 1 2`   |
+///
+/// `Constant` values compare by bit pattern, so that `EdgeMode` (and a
+/// `Filter` carrying one) is `Eq` and `Hash`.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EdgeMode {
+    /// Fit one polynomial to the first (and last) full window and evaluate
+    /// it at each edge point. This is what `Filter::smooth` does.
+    #[default]
+    Interp,
+    /// Reflect the signal about its end points, excluding the end points.
+    Mirror,
+    /// Repeat the end points.
+    Nearest,
+    /// Pad with a fixed value.
+    Constant(f64),
+    /// Treat the signal as periodic.
+    Wrap,
+}
+
+impl PartialEq for EdgeMode {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for EdgeMode {}
+
+impl Hash for EdgeMode {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+impl EdgeMode {
+    /// The variant, and the bits of the padding value for `Constant`.
+    fn key(self) -> (u8, u64) {
+        match self {
+            EdgeMode::Interp => (0, 0),
+            EdgeMode::Mirror => (1, 0),
+            EdgeMode::Nearest => (2, 0),
+            EdgeMode::Constant(value) => (3, value.to_bits()),
+            EdgeMode::Wrap => (4, 0),
+        }
+    }
+
+    /// Value of `data` at `index`, which may lie up to any distance outside
+    /// `0..data.len()`, extended according to this mode. `data` must be
+    /// nonempty and the mode must not be `Interp`.
+    pub(crate) fn extend<T: Float>(self, data: &[T], index: isize) -> T {
+        let len = data.len() as isize;
+        if (0..len).contains(&index) {
+            return data[index as usize];
+        }
+        let index = match self {
+            EdgeMode::Interp => unreachable!("interp mode does not pad"),
+            EdgeMode::Constant(value) => return T::from(value).unwrap(),
+            EdgeMode::Nearest => index.clamp(0, len - 1),
+            EdgeMode::Wrap => index.rem_euclid(len),
+            EdgeMode::Mirror if len == 1 => 0,
+            EdgeMode::Mirror => {
+                let period = 2 * (len - 1);
+                let folded = index.rem_euclid(period);
+                if folded < len {
+                    folded
+                } else {
+                    period - folded
+                }
+            }
+        };
+        data[index as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::EdgeMode;
+
+    #[test]
+    fn extend_inside_is_identity() {
+        let data = [1.0, 2.0, 3.0];
+        for mode in [EdgeMode::Mirror, EdgeMode::Nearest, EdgeMode::Wrap] {
+            for i in 0..3 {
+                assert_eq!(mode.extend(&data, i), data[i as usize]);
+            }
+        }
+    }
+
+    #[test]
+    fn extend_pads_both_sides() {
+        let data = [1.0, 2.0, 3.0, 4.0];
+        let pad = |mode: EdgeMode| -> Vec<f64> {
+            [-2, -1, 4, 5]
+                .iter()
+                .map(|&i| mode.extend(&data, i))
+                .collect()
+        };
+        assert_eq!(pad(EdgeMode::Mirror), vec![3.0, 2.0, 3.0, 2.0]);
+        assert_eq!(pad(EdgeMode::Nearest), vec![1.0, 1.0, 4.0, 4.0]);
+        assert_eq!(pad(EdgeMode::Constant(9.0)), vec![9.0, 9.0, 9.0, 9.0]);
+        assert_eq!(pad(EdgeMode::Wrap), vec![3.0, 4.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn extend_mirror_beyond_length() {
+        let data = [1.0, 2.0];
+        let padded: Vec<f64> = (-3..5).map(|i| EdgeMode::Mirror.extend(&data, i)).collect();
+        assert_eq!(padded, vec![2.0, 1.0, 2.0, 1.0, 2.0, 1.0, 2.0, 1.0]);
+    }
+}
@@ -0,0 +1,60 @@
+use core::fmt;
+
+/// Why a set of filter parameters was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FilterError {
+    /// `degree >= window_length`: the window has fewer points than the
+    /// polynomial has coefficients.
+    DegreeExceedsWindow { degree: u64, window_length: usize },
+    /// `derivative > degree`: the derivative of the fit is identically zero.
+    DerivativeExceedsDegree { derivative: u64, degree: u64 },
+    /// `radius == 0`: a one-point window cannot smooth.
+    ZeroRadius,
+    /// An even `window_length` where only odd windows, with the point being
+    /// smoothed at the center, are accepted.
+    EvenWindowLength { window_length: usize },
+    /// The window is too large for its positions and lengths to be computed
+    /// without overflow.
+    RadiusTooLarge,
+    /// A sample spacing `delta` that is zero, negative, or not finite.
+    InvalidDelta,
+    /// A degree above `i32::MAX`, too large to use as an exponent.
+    DegreeTooLarge { degree: u64 },
+    /// The input has fewer than the `min_len` samples the filter's
+    /// `FallbackPolicy` allows.
+    InputTooShort { len: usize, min_len: usize },
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterError::DegreeExceedsWindow {
+                degree,
+                window_length,
+            } => write!(
+                f,
+                "degree {} needs more than the {} points in the window",
+                degree, window_length
+            ),
+            FilterError::DerivativeExceedsDegree { derivative, degree } => {
+                write!(f, "derivative {} exceeds degree {}", derivative, degree)
+            }
+            FilterError::ZeroRadius => write!(f, "radius must be at least 1"),
+            FilterError::EvenWindowLength { window_length } => {
+                write!(f, "window length {} must be odd", window_length)
+            }
+            FilterError::RadiusTooLarge => write!(f, "radius is too large"),
+            FilterError::InvalidDelta => write!(f, "delta must be positive and finite"),
+            FilterError::DegreeTooLarge { degree } => write!(f, "degree {} is too large", degree),
+            FilterError::InputTooShort { len, min_len } => write!(
+                f,
+                "input of {} samples is shorter than the {} the filter needs",
+                len, min_len
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FilterError {}
@@ -0,0 +1,19 @@
+/// What `Filter::smooth` does with inputs shorter than the window.
+///
+/// Set with `FilterBuilder::fallback_policy`. Only the `Interp` edge mode
+/// ever needs a full window; the padding modes of `EdgeMode` smooth inputs
+/// of any length and ignore this policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FallbackPolicy {
+    /// Shrink the radius to the widest window that fits, however small,
+    /// lowering the degree too if it no longer fits that window.
+    #[default]
+    Shrink,
+    /// Reject the input: `Filter::try_smooth` returns
+    /// `FilterError::InputTooShort` and `Filter::smooth` panics.
+    Error,
+    /// Shrink the radius, but not below the given floor; inputs too short
+    /// for a window of that radius are rejected as with `Error`.
+    ShrinkToMin(usize),
+}
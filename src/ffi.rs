@@ -0,0 +1,113 @@
+//! C interface. `include/savgol.h` is generated from this file by
+//! `cbindgen --config cbindgen.toml --output include/savgol.h`.
+//!
+//! Filters are created with `savgol_filter_new`, which returns an owned
+//! pointer that must be released with `savgol_filter_free`. Smoothing never
+//! allocates on the caller's behalf: the caller owns both the input and the
+//! output buffer, and the output must have room for `len` values.
+
+use alloc::boxed::Box;
+use core::ptr;
+use core::slice;
+
+use crate::filter::Filter;
+
+/// Creates a filter as `Filter::new(radius, degree, deriv)` does, or returns
+/// NULL if the parameters are invalid. Release it with `savgol_filter_free`.
+#[no_mangle]
+pub extern "C" fn savgol_filter_new(radius: usize, degree: u64, deriv: u64) -> *mut Filter {
+    match Filter::try_new(radius, degree, deriv) {
+        Ok(filter) => Box::into_raw(Box::new(filter)),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Smooths the `len` values at `input` into the `len` values at `out`.
+/// Returns 0 on success and -1 if any pointer is NULL.
+///
+/// # Safety
+///
+/// `filter` must come from `savgol_filter_new` and not have been freed.
+/// `input` must be valid for reading and `out` for writing `len` doubles,
+/// and the two buffers must not overlap.
+#[no_mangle]
+pub unsafe extern "C" fn savgol_filter_smooth(
+    filter: *const Filter,
+    input: *const f64,
+    len: usize,
+    out: *mut f64,
+) -> i32 {
+    if filter.is_null() || input.is_null() || out.is_null() {
+        return -1;
+    }
+    let filter = &*filter;
+    let data = slice::from_raw_parts(input, len);
+    let out = slice::from_raw_parts_mut(out, len);
+    filter.smooth_into(data, out);
+    0
+}
+
+/// Releases a filter from `savgol_filter_new`. Does nothing for NULL.
+///
+/// # Safety
+///
+/// `filter` must be NULL or come from `savgol_filter_new`, and must not be
+/// used again afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn savgol_filter_free(filter: *mut Filter) {
+    if !filter.is_null() {
+        drop(Box::from_raw(filter));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::ptr;
+
+    use approx::assert_relative_eq;
+
+    use super::{savgol_filter_free, savgol_filter_new, savgol_filter_smooth};
+    use crate::Filter;
+
+    #[test]
+    fn smooth_through_raw_pointers_matches_smooth() {
+        let data = [1.0, -2.0, 3.0, -4.0, 5.0, -6.0, 7.0];
+        let mut out = [0.0; 7];
+        let filter = savgol_filter_new(2, 2, 0);
+        assert!(!filter.is_null());
+        unsafe {
+            let status = savgol_filter_smooth(filter, data.as_ptr(), data.len(), out.as_mut_ptr());
+            assert_eq!(status, 0);
+            savgol_filter_free(filter);
+        }
+        for (actual, expected) in out.iter().zip(Filter::new(2, 2, 0).smooth(&data)) {
+            assert_relative_eq!(*actual, expected, epsilon = 1e-10);
+        }
+    }
+
+    #[test]
+    fn invalid_parameters_and_null_pointers_are_rejected() {
+        assert!(savgol_filter_new(0, 2, 0).is_null());
+        assert!(savgol_filter_new(2, 2, 3).is_null());
+
+        let data = [1.0, 2.0, 3.0];
+        let mut out = [0.0; 3];
+        let filter = savgol_filter_new(1, 1, 0);
+        unsafe {
+            assert_eq!(
+                savgol_filter_smooth(ptr::null(), data.as_ptr(), 3, out.as_mut_ptr()),
+                -1
+            );
+            assert_eq!(
+                savgol_filter_smooth(filter, ptr::null(), 3, out.as_mut_ptr()),
+                -1
+            );
+            assert_eq!(
+                savgol_filter_smooth(filter, data.as_ptr(), 3, ptr::null_mut()),
+                -1
+            );
+            savgol_filter_free(filter);
+            savgol_filter_free(ptr::null_mut());
+        }
+    }
+}
@@ -1,151 +1,3745 @@
+use alloc::collections::VecDeque;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+
+use num_traits::Float;
+
+use crate::banded::BandedMatrix;
+use crate::cached::CachedFilter;
+#[cfg(feature = "high-precision")]
+use crate::double_double::DoubleDouble;
+use crate::edge::EdgeMode;
+use crate::error::FilterError;
+use crate::fallback::FallbackPolicy;
+use crate::linalg;
 use crate::math;
+use crate::online::OnlineFilter;
+use crate::precomputed::{self, PrecomputedFilter};
+
+/// A Savitzky–Golay filter of a given radius, polynomial degree, and
+/// derivative order.
+///
+/// The window normally extends `radius` points either side of the point
+/// being smoothed, but `new_asymmetric` allows different extents before
+/// (`radius_left`) and after (`radius_right`) it. Filters from `new_window`
+/// with an even `window_length` instead evaluate each fit half a sample
+/// before the point, at the center of the window.
+///
+/// A `Filter` is just its parameters: weights are computed when it is
+/// applied. To smooth many inputs without recomputing them, build a
+/// `PrecomputedFilter` with `precompute`. Neither type caches anything
+/// behind a shared reference, so both are `Send + Sync` and one filter can
+/// be shared across threads.
+///
+/// Filters from `FilterBuilder` may also carry a sample spacing `delta`, which
+/// scales derivatives as in `smooth_scaled`, and an `edge_mode`, which
+/// `smooth`, `smooth_into`, `smooth_streaming_cb`, and `PrecomputedFilter` use
+/// in place of `EdgeMode::Interp`, and a `FallbackPolicy` for inputs shorter
+/// than the window. `delta` is validated to be positive and finite and
+/// compared by bit pattern, as are `EdgeMode::Constant` values, so `Filter`
+/// is `Eq` and `Hash` and can key a cache.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Filter {
+    radius_left: usize,
+    radius_right: usize,
+    degree: u64,     // n
+    derivative: u64, // s
+    /// Evaluate fits at `t - 1/2` rather than `t`.
+    half_step: bool,
+    /// Sample spacing; derivative weights are divided by `delta^derivative`.
+    delta: f64,
+    edge_mode: EdgeMode,
+    fallback_policy: FallbackPolicy,
+}
+
+impl PartialEq for Filter {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Filter {}
+
+impl Hash for Filter {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+/// The 5-point quadratic smoother of `savgol_5_2`.
+impl Default for Filter {
+    fn default() -> Self {
+        Filter::savgol_5_2()
+    }
+}
+
+/// `SavGol(window=5, degree=2, deriv=0) kernel=[-0.086, 0.343, 0.486, 0.343,
+/// -0.086]`, with the kernel from `coefficients` rounded to 3 decimals, or to
+/// the precision given, as in `{:.5}`.
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let precision = f.precision().unwrap_or(3);
+        write!(
+            f,
+            "SavGol(window={}, degree={}, deriv={}) kernel=[",
+            self.window_length(),
+            self.degree,
+            self.derivative
+        )?;
+        for (j, weight) in self.coefficients().iter().enumerate() {
+            if j > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{:.*}", precision, weight)?;
+        }
+        write!(f, "]")
+    }
+}
+
+/// Largest `radius_left + radius_right` accepted, leaving room to double
+/// positions within the window and to count past its end in `i64`.
+pub(crate) const MAX_SPAN: u64 = i64::MAX as u64 / 4;
+
+/// Largest degree accepted, so that degrees and derivative orders convert
+/// to the `i32` exponents of `powi` and the `i64` indices of the weights.
+pub(crate) const MAX_DEGREE: u64 = i32::MAX as u64;
+
+impl Filter {
+    /// Creates a filter, panicking if the parameters are invalid. See
+    /// `try_new` for the conditions checked.
+    pub fn new(radius: usize, degree: u64, derivative: u64) -> Self {
+        Filter::try_new(radius, degree, derivative).unwrap()
+    }
+
+    /// Creates a filter over a window of `2 * radius + 1` points that fits a
+    /// polynomial of `degree` and evaluates its `derivative`'th derivative.
+    ///
+    /// Returns an error unless `radius >= 1`, `degree <= 2 * radius`, and
+    /// `derivative <= degree`, `RadiusTooLarge` for a radius so large that
+    /// the window length would overflow, or `DegreeTooLarge` for a degree
+    /// above `i32::MAX`.
+    pub fn try_new(radius: usize, degree: u64, derivative: u64) -> Result<Self, FilterError> {
+        Filter::try_new_asymmetric(radius, radius, degree, derivative)
+    }
+
+    /// Creates a filter over `window_length` points, panicking if the
+    /// parameters are invalid. See `try_new_window`.
+    pub fn new_window(window_length: usize, degree: u64, derivative: u64) -> Self {
+        Filter::try_new_window(window_length, degree, derivative).unwrap()
+    }
+
+    /// Creates a filter over `window_length` points, which may be even.
+    ///
+    /// Odd lengths give the same filter as `try_new(window_length / 2, ..)`.
+    /// For an even length, output `k` is the fit over
+    /// `data[k - window_length / 2..k + window_length / 2]` evaluated at its
+    /// center, the half-integer position `k - 1/2`, as in SciPy.
+    ///
+    /// Returns an error unless `window_length >= 2`,
+    /// `degree < window_length`, and `derivative <= degree`.
+    pub fn try_new_window(
+        window_length: usize,
+        degree: u64,
+        derivative: u64,
+    ) -> Result<Self, FilterError> {
+        if window_length < 2 {
+            return Err(FilterError::ZeroRadius);
+        }
+        let radius_left = window_length / 2;
+        let radius_right = window_length - 1 - radius_left;
+        let filter = Filter::try_new_asymmetric(radius_left, radius_right, degree, derivative)?;
+        Ok(Filter {
+            half_step: window_length.is_multiple_of(2),
+            ..filter
+        })
+    }
+
+    /// Creates a filter over an odd `window_length` of points, i.e. with
+    /// `radius = (window_length - 1) / 2`, as SciPy and MATLAB specify it.
+    ///
+    /// Returns `FilterError::EvenWindowLength` for even lengths (see
+    /// `try_new_window` to accept them), and otherwise the errors of
+    /// `try_new`.
+    pub fn from_window_length(
+        window_length: usize,
+        degree: u64,
+        derivative: u64,
+    ) -> Result<Self, FilterError> {
+        if window_length.is_multiple_of(2) {
+            return Err(FilterError::EvenWindowLength { window_length });
+        }
+        Filter::try_new(window_length / 2, degree, derivative)
+    }
+
+    /// Creates a filter whose window covers `radius_left` points before and
+    /// `radius_right` points after each smoothed point, panicking if the
+    /// parameters are invalid. See `try_new_asymmetric`.
+    pub fn new_asymmetric(
+        radius_left: usize,
+        radius_right: usize,
+        degree: u64,
+        derivative: u64,
+    ) -> Self {
+        Filter::try_new_asymmetric(radius_left, radius_right, degree, derivative).unwrap()
+    }
+
+    /// Creates a filter whose window covers `radius_left` points before and
+    /// `radius_right` points after each smoothed point.
+    ///
+    /// Returns an error unless `radius_left + radius_right >= 1`,
+    /// `degree <= radius_left + radius_right`, and `derivative <= degree`,
+    /// `RadiusTooLarge` if the window is too large to index, or
+    /// `DegreeTooLarge` if the degree is above `i32::MAX`.
+    pub fn try_new_asymmetric(
+        radius_left: usize,
+        radius_right: usize,
+        degree: u64,
+        derivative: u64,
+    ) -> Result<Self, FilterError> {
+        let span = radius_left
+            .checked_add(radius_right)
+            .filter(|&span| span < usize::MAX && span as u64 <= MAX_SPAN)
+            .ok_or(FilterError::RadiusTooLarge)?;
+        if span == 0 {
+            return Err(FilterError::ZeroRadius);
+        }
+        if degree > MAX_DEGREE {
+            return Err(FilterError::DegreeTooLarge { degree });
+        }
+        if degree > span as u64 {
+            return Err(FilterError::DegreeExceedsWindow {
+                degree,
+                window_length: span + 1,
+            });
+        }
+        if derivative > degree {
+            return Err(FilterError::DerivativeExceedsDegree { derivative, degree });
+        }
+        Ok(Filter::new_unchecked(
+            radius_left,
+            radius_right,
+            degree,
+            derivative,
+        ))
+    }
+
+    /// The classic 5-point quadratic smoother, `Filter::new(2, 2, 0)`.
+    pub fn savgol_5_2() -> Self {
+        Filter::new(2, 2, 0)
+    }
+
+    /// The classic 7-point quadratic smoother, `Filter::new(3, 2, 0)`.
+    pub fn savgol_7_2() -> Self {
+        Filter::new(3, 2, 0)
+    }
+
+    /// A first-derivative filter, `Filter::new(radius, degree, 1)`, panicking
+    /// if `radius == 0`, `degree == 0`, or `degree > 2 * radius`.
+    pub fn first_derivative(radius: usize, degree: u64) -> Self {
+        Filter::new(radius, degree, 1)
+    }
+
+    /// Creates a filter without validating it, for the shrunken filters used
+    /// on inputs shorter than the window.
+    fn new_unchecked(
+        radius_left: usize,
+        radius_right: usize,
+        degree: u64,
+        derivative: u64,
+    ) -> Self {
+        Filter {
+            radius_left,
+            radius_right,
+            degree,
+            derivative,
+            half_step: false,
+            delta: 1.0,
+            edge_mode: EdgeMode::Interp,
+            fallback_policy: FallbackPolicy::Shrink,
+        }
+    }
+
+    /// This filter with the sample spacing and edge mode set, for
+    /// `FilterBuilder`.
+    pub(crate) fn with_options(self, delta: f64, edge_mode: EdgeMode) -> Filter {
+        Filter {
+            delta,
+            edge_mode,
+            ..self
+        }
+    }
+
+    /// This filter with the fallback policy set, for `FilterBuilder`.
+    pub(crate) fn with_fallback_policy(self, fallback_policy: FallbackPolicy) -> Filter {
+        Filter {
+            fallback_policy,
+            ..self
+        }
+    }
+
+    /// This filter with a different derivative order, which must not exceed
+    /// the degree.
+    pub(crate) fn with_derivative(&self, derivative: u64) -> Filter {
+        Filter {
+            derivative,
+            ..*self
+        }
+    }
+
+    /// Points on each side of a symmetric window. For windows from
+    /// `new_asymmetric` or an even `new_window`, this is the larger side; see
+    /// `radius_left` and `radius_right`.
+    pub fn radius(&self) -> usize {
+        self.radius_left.max(self.radius_right)
+    }
+
+    /// Points in the window before the point being smoothed.
+    pub fn radius_left(&self) -> usize {
+        self.radius_left
+    }
+
+    /// Points in the window after the point being smoothed.
+    pub fn radius_right(&self) -> usize {
+        self.radius_right
+    }
+
+    /// Degree of the fitted polynomial.
+    pub fn degree(&self) -> u64 {
+        self.degree
+    }
+
+    /// Order of the derivative evaluated, 0 for smoothing.
+    pub fn derivative(&self) -> u64 {
+        self.derivative
+    }
+
+    /// Spacing between samples, 1 unless set with `FilterBuilder::delta`.
+    pub fn delta(&self) -> f64 {
+        self.delta
+    }
+
+    /// Every setting, with `delta` as its bits, for `Eq` and `Hash`.
+    fn key(&self) -> (usize, usize, u64, u64, bool, u64, EdgeMode, FallbackPolicy) {
+        (
+            self.radius_left,
+            self.radius_right,
+            self.degree,
+            self.derivative,
+            self.half_step,
+            self.delta.to_bits(),
+            self.edge_mode,
+            self.fallback_policy,
+        )
+    }
+
+    /// How `smooth` treats the edges, `Interp` unless set with
+    /// `FilterBuilder::edge_mode`.
+    pub fn edge_mode(&self) -> EdgeMode {
+        self.edge_mode
+    }
+
+    /// What `smooth` does with inputs shorter than the window, `Shrink`
+    /// unless set with `FilterBuilder::fallback_policy`.
+    pub fn fallback_policy(&self) -> FallbackPolicy {
+        self.fallback_policy
+    }
+
+    /// `delta^derivative`, which derivative weights are divided by.
+    fn derivative_scale(&self, derivative: u64) -> f64 {
+        self.delta.powi(derivative as i32)
+    }
+
+    /// Number of points in the window, `radius_left + radius_right + 1`.
+    pub fn window_length(&self) -> usize {
+        self.radius_left + self.radius_right + 1
+    }
+
+    /// Checks an input of `len` points against the fallback policy.
+    pub(crate) fn check_len(&self, len: usize) -> Result<(), FilterError> {
+        let min_len = match self.fallback_policy {
+            FallbackPolicy::Shrink => return Ok(()),
+            FallbackPolicy::Error => self.window_length(),
+            FallbackPolicy::ShrinkToMin(radius) => radius
+                .saturating_mul(2)
+                .saturating_add(1)
+                .min(self.window_length()),
+        };
+        if len < min_len && self.edge_mode == EdgeMode::Interp {
+            return Err(FilterError::InputTooShort { len, min_len });
+        }
+        Ok(())
+    }
+
+    /// The filter `smooth` falls back to for inputs of `len` points, when
+    /// `len` is shorter than the window: the widest odd window that fits,
+    /// split between the two sides in the same proportion as this one.
+    ///
+    /// A degree that no longer fits, above the new `span`, is lowered. A
+    /// smoothing filter then gets degree `span - 1`, so that it keeps
+    /// smoothing rather than interpolating the input, unless that would
+    /// drop below its derivative order; an interpolating one gets `span`.
+    pub(crate) fn shrunk(&self, len: usize) -> Filter {
+        let span = (len - 1) / 2 * 2;
+        let left = self.radius_left * span / (self.radius_left + self.radius_right);
+        let mut degree = self.degree;
+        if degree > span as u64 {
+            let most = if self.is_smoothing() {
+                span.saturating_sub(1)
+            } else {
+                span
+            };
+            degree = (most as u64).max(self.derivative).min(span as u64);
+        }
+        Filter {
+            radius_left: left,
+            radius_right: span - left,
+            degree,
+            ..*self
+        }
+    }
+
+    /// Shared factorial and Gram factor table for this filter's window.
+    fn weight_table(&self) -> math::WeightTable {
+        math::WeightTable::new(
+            (self.radius_left + self.radius_right) as i64,
+            self.degree as i64,
+        )
+    }
+
+    /// Weights for the fit at `t`, one kernel per entry of `orders`.
+    fn kernels_from(&self, table: &math::WeightTable, t: i64, orders: &[u64]) -> Vec<Vec<f64>> {
+        if self.degree == 0 {
+            // A constant fit is the window mean wherever it is evaluated, and
+            // its derivatives vanish.
+            let mean = 1.0 / self.window_length() as f64;
+            return orders
+                .iter()
+                .map(|&order| vec![if order == 0 { mean } else { 0.0 }; self.window_length()])
+                .collect();
+        }
+        // Shift the window to be centered on zero, in half-sample units.
+        let shift = self.radius_right as i64 - self.radius_left as i64;
+        let orders_i64: Vec<i64> = orders.iter().map(|&order| order as i64).collect();
+        let per_point: Vec<Vec<f64>> = (-(self.radius_left as i64)..=self.radius_right as i64)
+            .map(|i| {
+                table.weights(
+                    2 * i - shift,
+                    2 * t - shift - self.half_step as i64,
+                    &orders_i64,
+                )
+            })
+            .collect();
+        orders
+            .iter()
+            .enumerate()
+            .map(|(o, &order)| {
+                let scale = self.derivative_scale(order);
+                per_point.iter().map(|weights| weights[o] / scale).collect()
+            })
+            .collect()
+    }
+
+    /// Weights applied to a full window when evaluating the fit at offset `t`.
+    fn kernel(&self, t: i64) -> Vec<f64> {
+        self.kernels_from(&self.weight_table(), t, &[self.derivative])
+            .swap_remove(0)
+    }
+
+    /// The convolution kernel applied to interior points: the weights for
+    /// `i = -radius_left..=radius_right`, in that order.
+    pub fn coefficients(&self) -> Vec<f64> {
+        self.kernel(0)
+    }
+
+    /// Make sure you have a window of size radius_left + radius_right + 1
+    fn smooth_point(&self, t: i64, window: &[f64]) -> f64 {
+        assert!(window.len() == self.window_length());
+        let mut sum = math::CompensatedSum::<f64>::default();
+        for (weight, &value) in self.kernel(t).iter().zip(window) {
+            sum.add(weight * value);
+        }
+        sum.value()
+    }
+
+    /// Weights for the fit at `t` when window point `j` counts with weight
+    /// `point_weights[j]`, or `None` if too few points have nonzero weight.
+    fn weighted_kernel(&self, t: i64, point_weights: &[f64]) -> Option<Vec<f64>> {
+        // Positions relative to the window center, as in `weight`.
+        let center = (self.radius_right as f64 - self.radius_left as f64) / 2.0;
+        let xs: Vec<f64> = (-(self.radius_left as i64)..=self.radius_right as i64)
+            .map(|i| i as f64 - center)
+            .collect();
+        let t = t as f64 - center - if self.half_step { 0.5 } else { 0.0 };
+        let scale = self.derivative_scale(self.derivative);
+        math::weights_weighted(
+            &xs,
+            point_weights,
+            self.degree as i64,
+            t,
+            self.derivative as i64,
+        )
+        .map(|kernel| kernel.into_iter().map(|w| w / scale).collect())
+    }
+
+    /// The window `smooth` uses for output `k` of `len` points, as its start
+    /// index and the position `t` of `k` within it. `len` must be at least
+    /// the window length.
+    fn window_for(&self, len: usize, k: usize) -> (usize, i64) {
+        let start = k
+            .saturating_sub(self.radius_left)
+            .min(len - self.window_length());
+        (start, (k - start) as i64 - self.radius_left as i64)
+    }
+
+    /// The weights applied to a full window to evaluate its fit at offset
+    /// `t` from the window's center, for `t` in `-radius_left..=radius_right`.
+    ///
+    /// `smooth` uses these for the first `radius_left` outputs (negative `t`)
+    /// and the last `radius_right` (positive `t`); `edge_coefficients(0)` is
+    /// `coefficients()`.
+    pub fn edge_coefficients(&self, t: i64) -> Vec<f64> {
+        assert!((-(self.radius_left as i64)..=self.radius_right as i64).contains(&t));
+        self.kernel(t)
+    }
+
+    /// The weights giving the first derivative of the fit at the window's
+    /// last sample, for `OnlineFilter::push_derivative`. Unlike
+    /// `edge_coefficients`, this is at the sample itself even for even
+    /// windows from `new_window`, whose fits are otherwise evaluated half a
+    /// sample early.
+    pub(crate) fn newest_slope_coefficients(&self) -> Vec<f64> {
+        let filter = Filter {
+            half_step: false,
+            ..self.with_derivative(1)
+        };
+        filter.kernel(self.radius_right as i64)
+    }
+
+    /// The 2-norm condition number of the normal equations `AᵀA` for fitting
+    /// this window's samples with monomials up to `degree`, positions scaled
+    /// to `[-1, 1]`, as in `condition_number`.
+    ///
+    /// The weights come from Gram polynomials and never solve these
+    /// equations, so this measures how ill-posed the monomial fit itself is,
+    /// e.g. how sensitive the coefficients from `fit` are to the data. It
+    /// grows quickly with degree; near `1 / f64::EPSILON` the fit is
+    /// numerically meaningless and a lower degree is advisable.
+    pub fn condition_number_estimate(&self) -> f64 {
+        let span = (self.radius_left + self.radius_right) as f64;
+        let scale = (span / 2.0).max(1.0);
+        let rows: Vec<Vec<f64>> = (0..self.window_length())
+            .map(|j| {
+                let x = (j as f64 - span / 2.0) / scale;
+                (0..=self.degree).map(|k| x.powi(k as i32)).collect()
+            })
+            .collect();
+        linalg::condition_number(linalg::normal_matrix(&rows))
+    }
+
+    /// Whether the fit actually smooths, i.e. the polynomial has fewer
+    /// coefficients (`degree + 1`) than the window has points:
+    /// `degree < radius_left + radius_right`.
+    ///
+    /// A polynomial of degree `window_length - 1` passes through every
+    /// point of the window, so with `degree == 2 * radius` the filter
+    /// returns its input unchanged (and a derivative filter reduces to a
+    /// finite-difference stencil with no noise reduction).
+    pub fn is_smoothing(&self) -> bool {
+        (self.degree as usize) < self.radius_left + self.radius_right
+    }
+
+    /// Sum of `coefficients()`. It is 1 for smoothing, since the fit
+    /// reproduces a constant, and 0 for every derivative of one.
+    pub fn kernel_sum(&self) -> f64 {
+        self.coefficients().iter().sum()
+    }
+
+    /// Checks that `kernel_sum` is 1 for smoothing or 0 for a derivative, to
+    /// within rounding relative to the size of the weights. A failure means
+    /// the weights have lost precision, e.g. at a very high degree.
+    pub fn verify_normalization(&self) -> bool {
+        let coefficients = self.coefficients();
+        let scale = coefficients.iter().map(|w| w.abs()).sum::<f64>().max(1.0);
+        let expected = if self.derivative == 0 { 1.0 } else { 0.0 };
+        (coefficients.iter().sum::<f64>() - expected).abs() <= 1e-9 * scale
+    }
+
+    /// Magnitude of the centered filter's frequency response at a single
+    /// frequency, i.e. how much a sinusoid at that frequency is scaled by.
+    ///
+    /// `normalized_freq` is in cycles per sample, from 0 (DC) to 0.5 (the
+    /// Nyquist frequency). Multiply by 2π to convert to radians per sample.
+    pub fn attenuation_at(&self, normalized_freq: f64) -> f64 {
+        self.magnitude_of(&self.coefficients(), normalized_freq)
+    }
+
+    /// `(normalized_frequency, magnitude)` pairs at `n_points` evenly spaced
+    /// frequencies from 0 to 0.5 cycles per sample inclusive, as given by
+    /// `attenuation_at`.
+    pub fn frequency_response(&self, n_points: usize) -> Vec<(f64, f64)> {
+        let coefficients = self.coefficients();
+        let step = 0.5 / n_points.saturating_sub(1).max(1) as f64;
+        (0..n_points)
+            .map(|k| {
+                let freq = k as f64 * step;
+                (freq, self.magnitude_of(&coefficients, freq))
+            })
+            .collect()
+    }
+
+    fn magnitude_of(&self, coefficients: &[f64], normalized_freq: f64) -> f64 {
+        let omega = 2.0 * core::f64::consts::PI * normalized_freq;
+        let (mut re, mut im) = (0.0, 0.0);
+        let offsets = -(self.radius_left as i64)..=self.radius_right as i64;
+        for (i, w) in offsets.zip(coefficients) {
+            re += w * (omega * i as f64).cos();
+            im -= w * (omega * i as f64).sin();
+        }
+        re.hypot(im)
+    }
+
+    /// The linear operator applied by `smooth` to data of length `n`, in
+    /// banded form, with `EdgeMode::Interp` edges whatever this filter's
+    /// edge mode: `matrix.apply(data)` equals
+    /// `smooth_with(data, EdgeMode::Interp)`. The padding modes have no such
+    /// band, as `Wrap` couples the two ends and `Constant` is not linear.
+    pub fn smoothing_matrix_banded(&self, n: usize) -> BandedMatrix {
+        if self.edge_mode != EdgeMode::Interp {
+            let interp = Filter {
+                edge_mode: EdgeMode::Interp,
+                ..*self
+            };
+            return interp.smoothing_matrix_banded(n);
+        }
+        if n <= 2 {
+            // Read the columns off by smoothing unit vectors.
+            let columns: Vec<Vec<f64>> = (0..n)
+                .map(|col| {
+                    let mut unit = vec![0.0; n];
+                    unit[col] = 1.0;
+                    self.smooth(&unit)
+                })
+                .collect();
+            let rows = (0..n)
+                .map(|row| columns.iter().map(|column| column[row]).collect())
+                .collect();
+            return BandedMatrix::dense(rows);
+        }
+        if n < self.window_length() {
+            return self.shrunk(n).smoothing_matrix_banded(n);
+        }
+        let precomputed = self.precompute();
+        BandedMatrix::new(n, precomputed.kernel, precomputed.head, precomputed.tail)
+    }
+
+    /// The dense `n x n` matrix `M` with
+    /// `smooth_with(data, EdgeMode::Interp) == M * data` for data of length
+    /// `n`: the interior kernel on the interior rows and the `Interp` edge
+    /// weights on the first and last rows, whatever this filter's edge mode.
+    /// See `smoothing_matrix_banded` for a compact form.
+    pub fn matrix(&self, n: usize) -> Vec<Vec<f64>> {
+        let banded = self.smoothing_matrix_banded(n);
+        (0..n)
+            .map(|row| (0..n).map(|col| banded.get(row, col)).collect())
+            .collect()
+    }
+
+    /// Computes the interior kernel and edge weight rows once, so that
+    /// repeated smoothing does not recompute any weights.
+    pub fn precompute(&self) -> PrecomputedFilter {
+        let table = self.weight_table();
+        let mut kernel = |t| {
+            self.kernels_from(&table, t, &[self.derivative])
+                .swap_remove(0)
+        };
+        PrecomputedFilter {
+            filter: *self,
+            head: (-(self.radius_left as i64)..0).map(&mut kernel).collect(),
+            kernel: kernel(0),
+            tail: (1..=self.radius_right as i64).map(&mut kernel).collect(),
+        }
+    }
+
+    /// Wraps this filter in a `CachedFilter`, which keeps the weights it
+    /// computes for each input length across calls.
+    pub fn into_cached(self) -> CachedFilter {
+        CachedFilter::new(self)
+    }
+
+    /// Smooths `data`, returning one output per input sample. Weights are
+    /// computed in `f64` and cast to `T` when applied.
+    ///
+    /// A derivative output is the `derivative`'th derivative of the fit
+    /// itself, not the coefficient of its Taylor term (which is smaller by
+    /// `derivative!`), per unit of the filter's `delta`: the per-sample
+    /// value divided by `delta^derivative`. With the default `delta = 1.0`
+    /// that is with respect to the sample index; use `FilterBuilder::delta`
+    /// or `smooth_scaled` for other spacings.
+    ///
+    /// An empty input gives an empty output. A single sample is returned
+    /// unchanged when smoothing, and every derivative of it is 0.
+    ///
+    /// # Panics
+    ///
+    /// If the fallback policy rejects `data` as too short; see `try_smooth`.
+    pub fn smooth<T: Float>(&self, data: &[T]) -> Vec<T> {
+        let mut smoothed = vec![T::zero(); data.len()];
+        self.smooth_into(data, &mut smoothed);
+        smoothed
+    }
+
+    /// Like `smooth`, but returns `FilterError::InputTooShort` instead of
+    /// panicking when the fallback policy rejects `data`.
+    pub fn try_smooth<T: Float>(&self, data: &[T]) -> Result<Vec<T>, FilterError> {
+        self.check_len(data.len())?;
+        Ok(self.smooth(data))
+    }
+
+    /// Like `smooth`, but writes the result into `out`, which must be the
+    /// same length as `data`. Reusing `out` across calls avoids allocating.
+    pub fn smooth_into<T: Float>(&self, data: &[T], out: &mut [T]) {
+        assert!(out.len() == data.len());
+        self.smooth_streaming_cb(data, |index, value| out[index] = value);
+    }
+
+    /// The fit over `window`, a full window of `radius_left + radius_right +
+    /// 1` samples, evaluated at offset `t` from the window's smoothed point,
+    /// for `t` in `-radius_left..=radius_right`.
+    ///
+    /// For callers keeping their own ring buffer: the deque is read in place
+    /// through `as_slices`, so it need not be contiguous.
+    pub fn smooth_window(&self, window: &VecDeque<f64>, t: i64) -> f64 {
+        assert_eq!(window.len(), self.window_length());
+        assert!((-(self.radius_left as i64)..=self.radius_right as i64).contains(&t));
+        let (front, back) = window.as_slices();
+        let mut sum = math::CompensatedSum::<f64>::default();
+        for (weight, &value) in self.kernel(t).iter().zip(front.iter().chain(back)) {
+            sum.add(weight * value);
+        }
+        sum.value()
+    }
+
+    /// Lazily yields the values of `smooth(data)` in order, computing each
+    /// only when it is pulled, e.g. to stop at the first one that matters.
+    ///
+    /// The weights are computed up front, as for `precompute`. Inputs of at
+    /// most one sample, and inputs too short for a window with
+    /// `EdgeMode::Interp` edges, are smoothed eagerly by `smooth`, since
+    /// they are shorter than the window anyway.
+    pub fn iter_smoothed<'a>(&'a self, data: &'a [f64]) -> impl Iterator<Item = f64> + 'a {
+        let len = self.window_length();
+        let eager = data.len() <= 1 || (self.edge_mode == EdgeMode::Interp && data.len() < len);
+        let (smoothed, lazy_len) = if eager {
+            (self.smooth(data), 0)
+        } else {
+            (Vec::new(), data.len())
+        };
+        let precomputed = (lazy_len > 0).then(|| self.precompute());
+        let lazy = (0..lazy_len).map(move |k| {
+            let precomputed = precomputed.as_ref().unwrap();
+            if self.edge_mode == EdgeMode::Interp {
+                let (start, t) = self.window_for(data.len(), k);
+                return precomputed::dot(precomputed.row(t), &data[start..start + len]);
+            }
+            let mut sum = math::CompensatedSum::<f64>::default();
+            for (j, weight) in precomputed.row(0).iter().enumerate() {
+                let index = (k + j) as isize - self.radius_left as isize;
+                sum.add(weight * self.edge_mode.extend(data, index));
+            }
+            sum.value()
+        });
+        smoothed.into_iter().chain(lazy)
+    }
+
+    /// `smooth` for any container of samples, such as a `Vec`, an array, or
+    /// a `Box<[f64]>`, without an explicit `as_slice`.
+    pub fn smooth_from<I: AsRef<[f64]>>(&self, data: I) -> Vec<f64> {
+        self.smooth(data.as_ref())
+    }
+
+    /// Smooths the values `get` extracts from each element of `data`, such
+    /// as one field of a slice of structs.
+    pub fn smooth_by<T, F: Fn(&T) -> f64>(&self, data: &[T], get: F) -> Vec<f64> {
+        let values: Vec<f64> = data.iter().map(get).collect();
+        self.smooth(&values)
+    }
+
+    /// `smooth` for a fixed-size array, without heap allocation: instead of
+    /// precomputing kernel rows, each weight is computed as it is needed
+    /// with stack buffers of length `N`.
+    ///
+    /// Each call computes O(window_length^2) weights, so this is slower
+    /// than `smooth` for wide windows. Falls back to `smooth`, which
+    /// allocates, when the derivative order is `N` or more.
+    pub fn smooth_array<const N: usize>(&self, data: &[f64; N]) -> [f64; N] {
+        let mut out = [0.0; N];
+        let tiny = N <= 1 || (N == 2 && self.edge_mode == EdgeMode::Interp);
+        if tiny || self.derivative as usize >= N {
+            // The short-input cases of `smooth` do not allocate.
+            self.smooth_streaming_cb(data, |k, value| out[k] = value);
+            return out;
+        }
+        let offsets = || -(self.radius_left as i64)..=self.radius_right as i64;
+        if self.edge_mode != EdgeMode::Interp {
+            for i in offsets() {
+                let weight = self.weight_on_stack::<N>(i, 0);
+                for (k, value) in out.iter_mut().enumerate() {
+                    *value += weight * self.edge_mode.extend(data, k as isize + i as isize);
+                }
+            }
+            return out;
+        }
+        let filter = if N < self.window_length() {
+            self.shrunk(N)
+        } else {
+            *self
+        };
+        let (left, right) = (filter.radius_left, filter.radius_right);
+        for t in -(left as i64)..=right as i64 {
+            let outputs = match t {
+                0 => left..N - right,
+                t if t < 0 => (left as i64 + t) as usize..(left as i64 + t + 1) as usize,
+                t => N - 1 - right + t as usize..N - right + t as usize,
+            };
+            for i in -(left as i64)..=right as i64 {
+                let weight = filter.weight_on_stack::<N>(i, t);
+                for k in outputs.clone() {
+                    let (start, _) = filter.window_for(N, k);
+                    out[k] += weight * data[start + (i + left as i64) as usize];
+                }
+            }
+        }
+        out
+    }
+
+    /// One weight of `kernel(t)`, computed as in `smooth_array`.
+    fn weight_on_stack<const N: usize>(&self, i: i64, t: i64) -> f64 {
+        if self.degree == 0 {
+            return 1.0 / self.window_length() as f64;
+        }
+        let shift = self.radius_right as i64 - self.radius_left as i64;
+        math::weight_doubled_on_stack::<N>(
+            2 * i - shift,
+            (self.radius_left + self.radius_right) as i64,
+            self.degree as i64,
+            2 * t - shift - self.half_step as i64,
+            self.derivative as i64,
+        ) / self.derivative_scale(self.derivative)
+    }
+
+    /// `smooth` for integer samples such as `i32` or `u16` readings, cast to
+    /// `f64` first.
+    pub fn smooth_ints<T: Into<f64> + Copy>(&self, data: &[T]) -> Vec<f64> {
+        let data: Vec<f64> = data.iter().map(|&x| x.into()).collect();
+        self.smooth(&data)
+    }
+
+    /// `smooth` with the weights and each weighted sum computed in
+    /// double-double arithmetic (about 32 significant digits) and only the
+    /// outputs rounded to `f64`.
+    ///
+    /// Several times slower than `smooth`, so worth it only for high
+    /// degrees over wide windows, where the `f64` weights lose digits and
+    /// the alternating-sign sums cancel.
+    #[cfg(feature = "high-precision")]
+    pub fn smooth_high_precision(&self, data: &[f64]) -> Vec<f64> {
+        let len = self.window_length();
+        if self.edge_mode != EdgeMode::Interp && data.len() >= 2 {
+            let kernel = self.kernel_dd(0);
+            return (0..data.len() as isize)
+                .map(|k| {
+                    let window = (k - self.radius_left as isize..)
+                        .map(|index| self.edge_mode.extend(data, index));
+                    dot_dd(&kernel, window)
+                })
+                .collect();
+        }
+        if data.len() <= 2 {
+            return self.smooth(data);
+        }
+        if data.len() < len {
+            return self.shrunk(data.len()).smooth_high_precision(data);
+        }
+        let rows: Vec<Vec<DoubleDouble>> = (-(self.radius_left as i64)..=self.radius_right as i64)
+            .map(|t| self.kernel_dd(t))
+            .collect();
+        (0..data.len())
+            .map(|k| {
+                let (start, t) = self.window_for(data.len(), k);
+                let row = &rows[(t + self.radius_left as i64) as usize];
+                dot_dd(row, data[start..start + len].iter().copied())
+            })
+            .collect()
+    }
+
+    /// `kernel(t)` in double-double arithmetic.
+    #[cfg(feature = "high-precision")]
+    fn kernel_dd(&self, t: i64) -> Vec<DoubleDouble> {
+        let len = DoubleDouble::from(self.window_length() as f64);
+        let scale = (0..self.derivative).fold(DoubleDouble::from(1.0), |scale, _| {
+            scale * self.delta.into()
+        });
+        let shift = self.radius_right as i64 - self.radius_left as i64;
+        (-(self.radius_left as i64)..=self.radius_right as i64)
+            .map(|i| {
+                if self.degree == 0 {
+                    return DoubleDouble::from(1.0) / len;
+                }
+                math::weights_doubled_dd(
+                    2 * i - shift,
+                    (self.radius_left + self.radius_right) as i64,
+                    self.degree as i64,
+                    2 * t - shift - self.half_step as i64,
+                    self.derivative as i64,
+                ) / scale
+            })
+            .collect()
+    }
+
+    /// Smooths a `width x height` image stored row by row by applying this
+    /// filter along each row and then along each column.
+    ///
+    /// This is a least-squares fit over the square neighborhood by the
+    /// tensor product of the 1-D polynomials, which allows terms up to
+    /// `x^degree * y^degree`, whereas `Filter2D` bounds the total degree.
+    /// Both reproduce polynomials of total degree up to `degree`, and the
+    /// separable filter costs `2 * window_length` rather than
+    /// `window_length^2` operations per point. With a nonzero derivative
+    /// order both passes differentiate, giving the mixed derivative
+    /// `d^(2s) / dx^s dy^s`; for a derivative along one axis only, use
+    /// `Filter2D`.
+    pub fn smooth_separable_2d(&self, data: &[f64], width: usize, height: usize) -> Vec<f64> {
+        assert_eq!(data.len(), width * height);
+        let precomputed = self.precompute();
+        let mut smoothed = Vec::with_capacity(data.len());
+        for row in data.chunks_exact(width.max(1)) {
+            smoothed.extend(precomputed.smooth(row));
+        }
+        let mut column = Vec::with_capacity(height);
+        for x in 0..width {
+            column.clear();
+            column.extend((0..height).map(|y| smoothed[y * width + x]));
+            for (y, value) in precomputed.smooth(&column).into_iter().enumerate() {
+                smoothed[y * width + x] = value;
+            }
+        }
+        smoothed
+    }
+
+    /// Smooths `data`, handling the points near each end according to `mode`.
+    /// `EdgeMode::Interp` is the same as `smooth`.
+    ///
+    /// The padding modes apply the centered kernel at every point, so unlike
+    /// `Interp` they never shrink the radius for short inputs.
+    pub fn smooth_with<T: Float>(&self, data: &[T], mode: EdgeMode) -> Vec<T> {
+        if mode == EdgeMode::Interp || data.is_empty() {
+            let interp = Filter {
+                edge_mode: EdgeMode::Interp,
+                ..*self
+            };
+            return interp.smooth(data);
+        }
+        let end = data.len() + self.radius_right;
+        let padded: Vec<T> = (-(self.radius_left as isize)..end as isize)
+            .map(|index| mode.extend(data, index))
+            .collect();
+        let kernel = self.kernel(0);
+        padded
+            .windows(kernel.len())
+            .map(|window| precomputed::dot(&kernel, window))
+            .collect()
+    }
+
+    /// Smooths `data` sampled every `delta` units, so that derivatives are
+    /// with respect to that axis: each output of `smooth` is divided by
+    /// `delta.powi(derivative)`, on top of any spacing the filter was built
+    /// with.
+    pub fn smooth_scaled(&self, data: &[f64], delta: f64) -> Vec<f64> {
+        let scale = delta.powi(self.derivative as i32);
+        self.smooth(data)
+            .into_iter()
+            .map(|value| value / scale)
+            .collect()
+    }
+
+    /// Causal smoothing: `output[k]` uses only `data[..=k]`, fitting the last
+    /// window of samples and evaluating the fit at the newest one
+    /// (`t = radius_right`). The first `window_length - 1` outputs have less
+    /// history and use the largest window that fits, as `smooth` does for
+    /// short inputs.
+    ///
+    /// Polynomials up to `degree` are still reproduced without delay, but
+    /// unlike the centered filter this one is not zero-phase: other signal
+    /// components come out delayed, by up to about `radius` samples, and
+    /// noise is suppressed less than by the centered filter.
+    pub fn smooth_causal(&self, data: &[f64]) -> Vec<f64> {
+        let len = self.window_length();
+        let mut smoothed: Vec<f64> = (0..data.len().min(len - 1))
+            .map(|k| *self.smooth(&data[..=k]).last().unwrap())
+            .collect();
+        let newest = self.kernel(self.radius_right as i64);
+        for window in data.windows(len) {
+            smoothed.push(precomputed::dot(&newest, window));
+        }
+        smoothed
+    }
+
+    /// Zero-phase smoothing in the manner of SciPy's `filtfilt`: runs
+    /// `smooth_causal` forward over `data`, then again over the reversed
+    /// result, and reverses back, so the delays of the two passes cancel.
+    ///
+    /// The data is filtered twice, so the magnitude response is the square
+    /// of the causal filter's and noise is smoothed more heavily than by a
+    /// single pass. With a nonzero derivative order the two passes compound
+    /// (and the reversed pass flips the sign of odd orders), so this is
+    /// meant for derivative order 0.
+    pub fn smooth_filtfilt(&self, data: &[f64]) -> Vec<f64> {
+        let mut forward = self.smooth_causal(data);
+        forward.reverse();
+        let mut smoothed = self.smooth_causal(&forward);
+        smoothed.reverse();
+        smoothed
+    }
+
+    /// Computes the filter's derivative of `data` with sample spacing `dx` and
+    /// writes it back into `data`, with `EdgeMode::Interp` edges whatever
+    /// this filter's edge mode.
+    ///
+    /// Only a rolling copy of one window of input samples is kept, so no
+    /// buffer the size of `data` is allocated.
+    pub fn derivative_in_place(&self, data: &mut [f64], dx: f64) {
+        let scale = dx.powi(self.derivative as i32);
+        let len = self.window_length();
+        if data.len() < len {
+            let derivative = self.smooth_with(data, EdgeMode::Interp);
+            for (value, d) in data.iter_mut().zip(derivative) {
+                *value = d / scale;
+            }
+            return;
+        }
+        let (radius_left, radius_right) = (self.radius_left, self.radius_right);
+        let mut window = data[..len].to_vec();
+        for t in -(radius_left as i64)..0 {
+            data[(t + radius_left as i64) as usize] = self.smooth_point(t, &window) / scale;
+        }
+        let kernel = self.kernel(0);
+        for i in radius_left..data.len() - radius_right {
+            data[i] = precomputed::dot(&kernel, &window) / scale;
+            if let Some(&next) = data.get(i + radius_right + 1) {
+                window.copy_within(1.., 0);
+                window[len - 1] = next;
+            }
+        }
+        let last = data.len() - radius_right - 1;
+        for t in 1..=radius_right as i64 {
+            data[last + t as usize] = self.smooth_point(t, &window) / scale;
+        }
+    }
+
+    /// Smooths `data` as `smooth` does with `EdgeMode::Interp` edges,
+    /// whatever this filter's edge mode, pairing each output with its
+    /// variance when the samples carry independent noise of variance
+    /// `noise_var`.
+    ///
+    /// An output `sum(w_i x_i)` has variance `noise_var * sum(w_i^2)`, taken
+    /// over the weights actually used for that point, so the variance grows
+    /// towards the edges where the fit is extrapolated.
+    pub fn smooth_with_variance(&self, data: &[f64], noise_var: f64) -> Vec<(f64, f64)> {
+        let matrix = self.smoothing_matrix_banded(data.len());
+        matrix
+            .apply(data)
+            .into_iter()
+            .enumerate()
+            .map(|(k, value)| {
+                let sum_of_squares: f64 = matrix.band(k).0.iter().map(|w| w * w).sum();
+                (value, noise_var * sum_of_squares)
+            })
+            .collect()
+    }
+
+    /// Smooths `data`, then clamps each step between consecutive outputs to
+    /// at most `max_delta` in a forward pass.
+    ///
+    /// Unlike `smooth` this is not a linear filter, and whenever the limit
+    /// binds the output lags behind the signal until it catches up. NaN
+    /// outputs are left as NaN and skipped, so the next step is limited
+    /// relative to the last non-NaN output.
+    ///
+    /// # Panics
+    ///
+    /// If `max_delta` is negative or NaN.
+    pub fn smooth_rate_limited(&self, data: &[f64], max_delta: f64) -> Vec<f64> {
+        assert!(max_delta >= 0.0, "max_delta must be non-negative");
+        let mut smoothed = self.smooth(data);
+        let mut previous = None;
+        for value in smoothed.iter_mut().filter(|value| !value.is_nan()) {
+            if let Some(previous) = previous {
+                *value = value.max(previous - max_delta).min(previous + max_delta);
+            }
+            previous = Some(*value);
+        }
+        smoothed
+    }
+
+    /// Picks the degree from `candidate_degrees` whose radius-`radius`
+    /// smoother best predicts `data` under leave-one-out cross-validation.
+    ///
+    /// For a linear smoother `y = H x` the prediction of `x_k` from the other
+    /// samples has residual `(x_k - y_k) / (1 - H_kk)`, so no refitting is
+    /// needed: each candidate costs one `smoothing_matrix_banded` and one
+    /// smoothing pass, about `O(n * window_length)`. The score is the mean
+    /// squared leave-one-out residual, with `EdgeMode::Interp` edges. Ties go
+    /// to the earlier candidate. Candidates that are invalid for the window
+    /// or that interpolate the data (`degree >= 2 * radius`, leaving
+    /// `H_kk = 1`) are skipped.
+    ///
+    /// # Panics
+    ///
+    /// If no candidate can be scored.
+    pub fn best_degree(data: &[f64], radius: usize, candidate_degrees: &[u64]) -> u64 {
+        let score = |degree: u64| -> Option<f64> {
+            if degree >= (radius as u64).saturating_mul(2) {
+                return None;
+            }
+            let matrix = Filter::try_new(radius, degree, 0)
+                .ok()?
+                .smoothing_matrix_banded(data.len());
+            let smoothed = matrix.apply(data);
+            let mut total = 0.0;
+            for (k, (x, y)) in data.iter().zip(&smoothed).enumerate() {
+                let (band, start) = matrix.band(k);
+                let leverage = band[k - start];
+                if leverage >= 1.0 - 1e-12 {
+                    return None;
+                }
+                let residual = (x - y) / (1.0 - leverage);
+                total += residual * residual;
+            }
+            Some(total / data.len() as f64)
+        };
+        candidate_degrees
+            .iter()
+            .filter_map(|&degree| Some((degree, score(degree)?)))
+            .fold(
+                None,
+                |best: Option<(u64, f64)>, (degree, error)| match best {
+                    Some((_, best_error)) if best_error <= error => best,
+                    _ => Some((degree, error)),
+                },
+            )
+            .expect("no candidate degree could be cross-validated")
+            .0
+    }
+
+    /// Estimates the noise level of `data` as the standard deviation of the
+    /// residuals `data - smooth(data)`, smoothing with derivative order 0
+    /// and `EdgeMode::Interp` edges whatever this filter's settings.
+    ///
+    /// A linear smoother `y = H x` uses up `tr(H)` degrees of freedom, so the
+    /// residual sum of squares is divided by `n - tr(H)` rather than `n - 1`:
+    /// `sqrt(sum(r_i^2) / (n - tr(H)))`. Returns NaN when the fit leaves no
+    /// degrees of freedom, e.g. when `data` is no longer than the window.
+    pub fn residual_std(&self, data: &[f64]) -> f64 {
+        let n = data.len();
+        let matrix = self.with_derivative(0).smoothing_matrix_banded(n);
+        let smoothed = matrix.apply(data);
+        let rss: f64 = data
+            .iter()
+            .zip(&smoothed)
+            .map(|(x, y)| (x - y) * (x - y))
+            .sum();
+        let trace: f64 = (0..n).map(|i| matrix.get(i, i)).sum();
+        let dof = n as f64 - trace;
+        if dof <= 1e-9 {
+            return f64::NAN;
+        }
+        (rss / dof).sqrt()
+    }
+
+    /// Smooths `data`, treating NaN samples as missing.
+    ///
+    /// Each window with missing samples is refit by least squares over only
+    /// the samples present, so a NaN no longer spreads to every output whose
+    /// window contains it. An output is NaN only when its window has too few
+    /// samples left to fit the degree. Windows without NaNs give the same
+    /// values as `smooth`.
+    pub fn smooth_nan(&self, data: &[f64]) -> Vec<f64> {
+        let present: Vec<f64> = data
+            .iter()
+            .map(|x| if x.is_nan() { 0.0 } else { 1.0 })
+            .collect();
+        let filled: Vec<f64> = data
+            .iter()
+            .map(|&x| if x.is_nan() { 0.0 } else { x })
+            .collect();
+        self.smooth_weighted(&filled, &present)
+    }
+
+    /// `smooth_nan`, also returning for each output the number of non-NaN
+    /// samples in the window it was fit over: `window_length` where no
+    /// samples are missing, fewer near gaps, and 0 where the output is NaN.
+    pub fn smooth_nan_with_counts(&self, data: &[f64]) -> (Vec<f64>, Vec<u32>) {
+        let smoothed = self.smooth_nan(data);
+        let filter = if data.len() < self.window_length() && !data.is_empty() {
+            self.shrunk(data.len())
+        } else {
+            *self
+        };
+        let len = filter.window_length();
+        let counts = smoothed
+            .iter()
+            .enumerate()
+            .map(|(k, value)| {
+                if value.is_nan() {
+                    return 0;
+                }
+                let (start, _) = filter.window_for(data.len(), k);
+                data[start..start + len]
+                    .iter()
+                    .filter(|x| !x.is_nan())
+                    .count() as u32
+            })
+            .collect();
+        (smoothed, counts)
+    }
+
+    /// Smooths `data` with a weighted least-squares fit in each window, where
+    /// `weights[j]` is the (nonnegative) weight of `data[j]`, e.g. the inverse
+    /// variance of its measurement error.
+    ///
+    /// Samples with larger weights pull the fit toward them, and samples with
+    /// zero weight are ignored. An output is NaN when its window has fewer
+    /// than `degree + 1` samples of nonzero weight. Windows whose weights are
+    /// all equal give the same values as `smooth`; every other window is
+    /// refit from scratch, costing O(window_length * degree) per point.
+    pub fn smooth_weighted(&self, data: &[f64], weights: &[f64]) -> Vec<f64> {
+        assert_eq!(data.len(), weights.len());
+        if data.len() < self.window_length() {
+            if data.is_empty() {
+                return Vec::new();
+            }
+            return self.shrunk(data.len()).smooth_weighted(data, weights);
+        }
+        let len = self.window_length();
+        let precomputed = self.precompute();
+        (0..data.len())
+            .map(|k| {
+                let (start, t) = self.window_for(data.len(), k);
+                let window = &data[start..start + len];
+                let window_weights = &weights[start..start + len];
+                if window_weights[0] != 0.0
+                    && window_weights.iter().all(|&w| w == window_weights[0])
+                {
+                    return precomputed::dot(precomputed.row(t), window);
+                }
+                match self.weighted_kernel(t, window_weights) {
+                    Some(kernel) => kernel
+                        .iter()
+                        .zip(window_weights)
+                        .zip(window)
+                        .filter(|((_, &w), _)| w != 0.0)
+                        .map(|((h, _), x)| h * x)
+                        .sum(),
+                    None => f64::NAN,
+                }
+            })
+            .collect()
+    }
+
+    /// Smooths `data` with a Gaussian-weighted least-squares fit: in each
+    /// window, the sample at offset `d` from the point being evaluated
+    /// counts with weight `exp(-d^2 / (2 sigma^2))`.
+    ///
+    /// Tapering the window this way lets distant samples fade out instead
+    /// of dropping off at the window's edge, which smooths the frequency
+    /// response and reduces ringing, but polynomials up to `degree` are
+    /// still reproduced exactly. Smaller `sigma` narrows the effective
+    /// window; as `sigma` grows the weights tend to 1 and the result to
+    /// `smooth`. The first and last points are handled as with
+    /// `EdgeMode::Interp`, with the taper centered on each edge point. A
+    /// `sigma` so small that fewer than `degree + 1` weights stay above
+    /// zero leaves the fit singular, and those outputs are NaN.
+    ///
+    /// # Panics
+    ///
+    /// If `sigma` is not positive, including NaN.
+    pub fn smooth_gaussian_weighted(&self, data: &[f64], sigma: f64) -> Vec<f64> {
+        assert!(sigma > 0.0, "sigma must be positive");
+        if data.len() < self.window_length() && data.len() > 2 {
+            return self
+                .shrunk(data.len())
+                .smooth_gaussian_weighted(data, sigma);
+        }
+        let filter = Filter {
+            edge_mode: EdgeMode::Interp,
+            ..*self
+        };
+        let half = if self.half_step { 0.5 } else { 0.0 };
+        let kernel = |t: i64| -> Vec<f64> {
+            let taper: Vec<f64> = (-(self.radius_left as i64)..=self.radius_right as i64)
+                .map(|i| {
+                    let offset = (i - t) as f64 + half;
+                    (-offset * offset / (2.0 * sigma * sigma)).exp()
+                })
+                .collect();
+            self.weighted_kernel(t, &taper)
+                .unwrap_or_else(|| vec![f64::NAN; taper.len()])
+        };
+        PrecomputedFilter {
+            filter,
+            head: (-(self.radius_left as i64)..0).map(kernel).collect(),
+            kernel: kernel(0),
+            tail: (1..=self.radius_right as i64).map(kernel).collect(),
+        }
+        .smooth(data)
+    }
+
+    /// Smooths `data` while down-weighting outliers, by iteratively
+    /// reweighted least squares as in LOWESS.
+    ///
+    /// Each of the `iterations` passes smooths with the current weights,
+    /// takes the residuals `r` from that fit and sets each sample's weight to
+    /// the bisquare `(1 - (r / 6s)^2)^2`, or 0 where `|r| >= 6s`, with `s` the
+    /// median absolute residual. The output is `smooth_weighted` with the
+    /// final weights, so with `iterations = 0` this is `smooth`. Iteration
+    /// stops early once `s` is zero, as the fit then already passes through
+    /// at least half of the samples.
+    pub fn smooth_robust(&self, data: &[f64], iterations: usize) -> Vec<f64> {
+        let smoother = self.with_derivative(0);
+        let mut weights = vec![1.0; data.len()];
+        for _ in 0..iterations {
+            let residuals: Vec<f64> = data
+                .iter()
+                .zip(smoother.smooth_weighted(data, &weights))
+                .map(|(x, fit)| x - fit)
+                .collect();
+            let mut magnitudes: Vec<f64> = residuals.iter().map(|r| r.abs()).collect();
+            magnitudes.sort_by(|a, b| a.total_cmp(b));
+            let scale = 6.0 * magnitudes.get(magnitudes.len() / 2).copied().unwrap_or(0.0);
+            if scale == 0.0 {
+                break;
+            }
+            for (weight, r) in weights.iter_mut().zip(&residuals) {
+                let u = r / scale;
+                *weight = if u.abs() < 1.0 {
+                    (1.0 - u * u) * (1.0 - u * u)
+                } else {
+                    0.0
+                };
+            }
+        }
+        self.smooth_weighted(data, &weights)
+    }
+
+    /// Smooths angles in radians, such as headings or phases, without the
+    /// artifacts of smoothing across the jump between `π` and `-π` (or `2π`
+    /// and 0). Returns angles in `(-π, π]`.
+    ///
+    /// The unit vectors `(cos, sin)` are smoothed componentwise and the
+    /// output is the direction of their smoothed sum, via `atan2`. Only
+    /// smoothing is supported: panics unless the derivative order is 0.
+    pub fn smooth_angular(&self, data: &[f64]) -> Vec<f64> {
+        assert!(
+            self.derivative == 0,
+            "smooth_angular does not support derivatives"
+        );
+        let precomputed = self.precompute();
+        let cos: Vec<f64> = data.iter().map(|angle| angle.cos()).collect();
+        let sin: Vec<f64> = data.iter().map(|angle| angle.sin()).collect();
+        precomputed
+            .smooth(&sin)
+            .into_iter()
+            .zip(precomputed.smooth(&cos))
+            .map(|(sin, cos)| {
+                let angle = sin.atan2(cos);
+                if angle == -core::f64::consts::PI {
+                    core::f64::consts::PI
+                } else {
+                    angle
+                }
+            })
+            .collect()
+    }
+
+    /// Smooths `data` with a window chosen per point between `min_radius`
+    /// and `max_radius`: wide where the signal is smooth, to remove more
+    /// noise, and narrow near sharp features such as peaks, to avoid
+    /// flattening them. The degree, derivative, and spacing are this
+    /// filter's, and its radius is ignored.
+    ///
+    /// The heuristic: the noise variance `σ²` is estimated with
+    /// `residual_std` at `min_radius`. Each point then takes the largest
+    /// radius `r` whose smoothing residuals over the `2r + 1` points around
+    /// it have mean square at most `2σ²`, since a fit that cannot follow
+    /// the local curvature leaves residuals well above the noise. Points
+    /// where no radius qualifies use `min_radius`.
+    ///
+    /// Costs one `smooth` per candidate radius. Panics if
+    /// `min_radius > max_radius` or a radius is too small for the degree.
+    pub fn smooth_adaptive(&self, data: &[f64], min_radius: usize, max_radius: usize) -> Vec<f64> {
+        assert!(min_radius <= max_radius);
+        let at_radius = |radius| {
+            Filter::new(radius, self.degree, self.derivative)
+                .with_options(self.delta, self.edge_mode)
+        };
+        let threshold = 2.0 * at_radius(min_radius).residual_std(data).powi(2);
+        let mut radii = vec![min_radius; data.len()];
+        for radius in min_radius + 1..=max_radius {
+            let fit = at_radius(radius).with_derivative(0).smooth(data);
+            // Prefix sums of squared residuals, for windowed means.
+            let mut prefix = Vec::with_capacity(data.len() + 1);
+            prefix.push(0.0);
+            for (x, y) in data.iter().zip(&fit) {
+                prefix.push(prefix[prefix.len() - 1] + (x - y) * (x - y));
+            }
+            for (k, chosen) in radii.iter_mut().enumerate() {
+                let start = k.saturating_sub(radius);
+                let end = (k + radius + 1).min(data.len());
+                let mean_square = (prefix[end] - prefix[start]) / (end - start) as f64;
+                if mean_square <= threshold {
+                    *chosen = radius;
+                }
+            }
+        }
+        let smoothed: Vec<Vec<f64>> = (min_radius..=max_radius)
+            .map(|radius| at_radius(radius).smooth(data))
+            .collect();
+        radii
+            .iter()
+            .enumerate()
+            .map(|(k, &radius)| smoothed[radius - min_radius][k])
+            .collect()
+    }
+
+    /// Smooths samples `y` taken at positions `x`, which need not be evenly
+    /// spaced but must be distinct.
+    ///
+    /// Each output fits a polynomial of the filter's degree to the window of
+    /// samples around it, in the local coordinate `x - x[k]`, and evaluates
+    /// it (or its derivative, per unit of `x`) at `x[k]`. Windows are chosen
+    /// by index exactly as in `smooth`, to which this reduces for `x = 0, 1,
+    /// 2, ...`.
+    pub fn smooth_xy(&self, x: &[f64], y: &[f64]) -> Vec<f64> {
+        assert_eq!(x.len(), y.len());
+        if y.len() < self.window_length() {
+            if y.is_empty() {
+                return Vec::new();
+            }
+            return self.shrunk(y.len()).smooth_xy(x, y);
+        }
+        let len = self.window_length();
+        let ones = vec![1.0; len];
+        (0..y.len())
+            .map(|k| {
+                let (start, _) = self.window_for(y.len(), k);
+                let xs: Vec<f64> = x[start..start + len].iter().map(|xj| xj - x[k]).collect();
+                let kernel = math::weights_weighted(
+                    &xs,
+                    &ones,
+                    self.degree as i64,
+                    0.0,
+                    self.derivative as i64,
+                )
+                .unwrap();
+                precomputed::dot(&kernel, &y[start..start + len])
+            })
+            .collect()
+    }
+
+    /// Smooths `data` once for each derivative order in `orders`, returning
+    /// the outputs in the same order. Each must be at most the degree, and
+    /// the filter's own derivative order is ignored.
+    ///
+    /// Equivalent to running a filter per order, but each weight shares its
+    /// Gram polynomial evaluations at the data point across the orders.
+    pub fn smooth_derivatives(&self, data: &[f64], orders: &[u64]) -> Vec<Vec<f64>> {
+        assert!(orders.iter().all(|&order| order <= self.degree));
+        if data.len() < self.window_length() {
+            return orders
+                .iter()
+                .map(|&order| self.with_derivative(order).smooth(data))
+                .collect();
+        }
+        let table = self.weight_table();
+        // rows[t][order] is the kernel for position t and that order.
+        let rows: Vec<Vec<Vec<f64>>> = (-(self.radius_left as i64)..=self.radius_right as i64)
+            .map(|t| self.kernels_from(&table, t, orders))
+            .collect();
+        (0..orders.len())
+            .map(|o| {
+                let mut kernels = rows.iter().map(|row| row[o].clone());
+                let precomputed = PrecomputedFilter {
+                    filter: self.with_derivative(orders[o]),
+                    head: kernels.by_ref().take(self.radius_left).collect(),
+                    kernel: kernels.next().unwrap(),
+                    tail: kernels.collect(),
+                };
+                precomputed.smooth(data)
+            })
+            .collect()
+    }
+
+    /// The smoothed value and first derivative at each point of `data`
+    /// sampled every `delta` units, as `[value, derivative]` pairs.
+    ///
+    /// The derivative is divided by `delta` as in `smooth_scaled`, on top of
+    /// any spacing the filter was built with. Both come from one pass of
+    /// `smooth_derivatives`, so the Gram polynomials are evaluated once for
+    /// the two orders. The filter's own derivative order is ignored.
+    ///
+    /// # Panics
+    ///
+    /// If the degree is 0.
+    pub fn smooth_value_and_derivative(&self, data: &[f64], delta: f64) -> Vec<[f64; 2]> {
+        let mut outputs = self.smooth_derivatives(data, &[0, 1]).into_iter();
+        let (values, slopes) = (outputs.next().unwrap(), outputs.next().unwrap());
+        values
+            .into_iter()
+            .zip(slopes)
+            .map(|(value, slope)| [value, slope / delta])
+            .collect()
+    }
+
+    /// The polynomial fit behind each output of `smooth`, as its coefficients
+    /// `a_0..=a_degree` in powers of the offset from the output's position.
+    ///
+    /// `a_0` is the smoothed value and `a_j` is the `j`'th derivative divided
+    /// by `j!`, so the fit near point `k` is `sum(a_j * (x - k)^j)`. The
+    /// filter's own derivative order is ignored. For even windows from
+    /// `new_window` the offset is measured from `k - 1/2` instead.
+    pub fn fit(&self, data: &[f64]) -> Vec<Vec<f64>> {
+        let orders: Vec<u64> = (0..=self.degree).collect();
+        let mut factorial = 1.0;
+        let derivatives: Vec<Vec<f64>> = self
+            .smooth_derivatives(data, &orders)
+            .into_iter()
+            .enumerate()
+            .map(|(j, derivative)| {
+                if j > 0 {
+                    factorial *= j as f64;
+                }
+                derivative.into_iter().map(|d| d / factorial).collect()
+            })
+            .collect();
+        (0..data.len())
+            .map(|k| derivatives.iter().map(|d| d[k]).collect())
+            .collect()
+    }
+
+    /// The smoothed cumulative integral of `data` sampled every `delta`
+    /// units, anchored so that `output[0] == 0`: `output[k]` integrates the
+    /// local fits from the first sample to sample `k`.
+    ///
+    /// Between samples `k` and `k + 1` the first half of the interval is
+    /// integrated under the fit around `k` and the second half under the fit
+    /// around `k + 1` (see `fit`). Any polynomial up to `degree` is
+    /// integrated exactly. The filter's derivative order is ignored, as is its
+    /// own spacing in favour of `delta`. Add a constant to anchor elsewhere.
+    pub fn integrate(&self, data: &[f64], delta: f64) -> Vec<f64> {
+        // Offset of sample k from the origin of its fit.
+        let shift = if self.half_step { 0.5 } else { 0.0 };
+        let integral = |coefficients: &[f64], from: f64, to: f64| -> f64 {
+            let (mut from_power, mut to_power) = (from, to);
+            let mut total = 0.0;
+            for (j, a) in coefficients.iter().enumerate() {
+                total += a * (to_power - from_power) / (j + 1) as f64;
+                from_power *= from;
+                to_power *= to;
+            }
+            total
+        };
+        let fits = self.with_options(1.0, self.edge_mode).fit(data);
+        let mut accumulated = 0.0;
+        let mut integrated = Vec::with_capacity(data.len());
+        integrated.extend(fits.first().map(|_| 0.0));
+        for pair in fits.windows(2) {
+            accumulated += integral(&pair[0], shift, shift + 0.5);
+            accumulated += integral(&pair[1], shift - 0.5, shift);
+            integrated.push(accumulated * delta);
+        }
+        integrated
+    }
+
+    /// Evaluates the local fits at fractional sample `positions`, e.g. to
+    /// upsample `data`.
+    ///
+    /// Each position uses the window whose center is nearest to it, so inside
+    /// the signal this is the same least-squares model as `smooth`, evaluated
+    /// between samples (and the filter's derivative order still applies).
+    /// Positions before the first or after the last window center all use
+    /// the first or last full window, which extrapolates past the ends of
+    /// `data` as `smooth` does at its edges.
+    pub fn interpolate(&self, data: &[f64], positions: &[f64]) -> Vec<f64> {
+        if data.len() < self.window_length() {
+            if data.is_empty() {
+                return vec![f64::NAN; positions.len()];
+            }
+            return self.shrunk(data.len()).interpolate(data, positions);
+        }
+        let len = self.window_length();
+        let center = (self.radius_right as f64 - self.radius_left as f64) / 2.0;
+        let ones = vec![1.0; len];
+        positions
+            .iter()
+            .map(|&position| {
+                let nearest = (position - center)
+                    .round()
+                    .clamp(0.0, (data.len() - 1) as f64);
+                let (start, _) = self.window_for(data.len(), nearest as usize);
+                let window_center = start as f64 + (len - 1) as f64 / 2.0;
+                let xs: Vec<f64> = (0..len)
+                    .map(|j| j as f64 - (len - 1) as f64 / 2.0)
+                    .collect();
+                let kernel = math::weights_weighted(
+                    &xs,
+                    &ones,
+                    self.degree as i64,
+                    position - window_center,
+                    self.derivative as i64,
+                )
+                .unwrap();
+                precomputed::dot(&kernel, &data[start..start + len])
+            })
+            .collect()
+    }
+
+    /// Smooths `data` like `smooth`, computing the interior points in
+    /// parallel on the rayon thread pool. The output is bit-identical to
+    /// `smooth`.
+    #[cfg(feature = "rayon")]
+    pub fn smooth_parallel(&self, data: &[f64]) -> Vec<f64> {
+        use rayon::prelude::*;
+
+        const CHUNK: usize = 4096;
+        let len = self.window_length();
+        if data.len() < len || self.edge_mode != EdgeMode::Interp {
+            return self.smooth(data);
+        }
+        let precomputed = self.precompute();
+        let mut smoothed = vec![0.0; data.len()];
+        let (head, rest) = smoothed.split_at_mut(self.radius_left);
+        let (interior, tail) = rest.split_at_mut(data.len() - len + 1);
+        for (value, row) in head.iter_mut().zip(&precomputed.head) {
+            *value = precomputed::dot(row, &data[..len]);
+        }
+        let kernel = &precomputed.kernel;
+        interior
+            .par_chunks_mut(CHUNK)
+            .enumerate()
+            .for_each(|(chunk, values)| {
+                for (j, value) in values.iter_mut().enumerate() {
+                    let start = chunk * CHUNK + j;
+                    *value = precomputed::dot(kernel, &data[start..start + len]);
+                }
+            });
+        for (value, row) in tail.iter_mut().zip(&precomputed.tail) {
+            *value = precomputed::dot(row, &data[data.len() - len..]);
+        }
+        smoothed
+    }
+
+    /// Smooths a long series delivered in pieces, such as blocks read from a
+    /// file, holding only one window of samples between chunks.
+    ///
+    /// Yields one `Vec` per input chunk with the outputs that became
+    /// available, then a final `Vec` with the last `radius_right` outputs
+    /// once `chunks` ends. Each output needs `radius_right` samples after
+    /// its own, so output lags input by that many samples and the first
+    /// window's worth comes out at once. Concatenated, the outputs equal
+    /// `smooth` of the concatenated chunks, with edges handled as
+    /// `EdgeMode::Interp` like `OnlineFilter`.
+    pub fn smooth_chunks<I: Iterator<Item = Vec<f64>>>(
+        &self,
+        chunks: I,
+    ) -> impl Iterator<Item = Vec<f64>> {
+        let mut online = OnlineFilter::new(self);
+        let mut chunks = chunks.fuse();
+        let mut started = false;
+        let mut finished = false;
+        core::iter::from_fn(move || {
+            if finished {
+                return None;
+            }
+            let Some(chunk) = chunks.next() else {
+                finished = true;
+                let rest = online.flush();
+                return (!rest.is_empty()).then_some(rest);
+            };
+            let mut output = Vec::with_capacity(chunk.len());
+            for sample in chunk {
+                if let Some(value) = online.push(sample) {
+                    if !started {
+                        output.extend_from_slice(online.leading_edge());
+                        started = true;
+                    }
+                    output.push(value);
+                }
+            }
+            Some(output)
+        })
+    }
+
+    /// Smooths each of many independent series, as `smooth` would, computing
+    /// the weights only once for all of them.
+    pub fn smooth_batch(&self, series: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        let precomputed = self.precompute();
+        series.iter().map(|data| precomputed.smooth(data)).collect()
+    }
+
+    /// `smooth_batch` with the series spread across the rayon thread pool,
+    /// all reading the same precomputed weights.
+    #[cfg(feature = "rayon")]
+    pub fn smooth_batch_parallel(&self, series: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        use rayon::prelude::*;
+
+        let precomputed = self.precompute();
+        series
+            .par_iter()
+            .map(|data| precomputed.smooth(data))
+            .collect()
+    }
+
+    /// Smooths `data`, calling `cb(index, value)` as soon as each output point
+    /// is computed. Points are emitted in index order and match `smooth`.
+    pub fn smooth_streaming_cb<T: Float>(&self, data: &[T], mut cb: impl FnMut(usize, T)) {
+        if let Err(error) = self.check_len(data.len()) {
+            panic!("{}", error);
+        }
+        match data {
+            [] => return,
+            // Only a constant fits one point, so every derivative is zero.
+            &[value] => {
+                cb(
+                    0,
+                    if self.derivative == 0 {
+                        value
+                    } else {
+                        T::zero()
+                    },
+                );
+                return;
+            }
+            _ => {}
+        }
+        if self.edge_mode != EdgeMode::Interp {
+            for (index, value) in self
+                .smooth_with(data, self.edge_mode)
+                .into_iter()
+                .enumerate()
+            {
+                cb(index, value);
+            }
+            return;
+        }
+        if data.len() == 2 {
+            // Too short for a window. Values pass through unchanged, and
+            // derivatives are those of the line through the two points (or
+            // of a constant for degree 0), so higher orders are zero.
+            for (index, &value) in data.iter().enumerate() {
+                let output = match self.derivative {
+                    0 => value,
+                    1 if self.degree >= 1 => (data[1] - data[0]) / T::from(self.delta).unwrap(),
+                    _ => T::zero(),
+                };
+                cb(index, output);
+            }
+            return;
+        }
+        if data.len() < self.window_length() {
+            return self.shrunk(data.len()).smooth_streaming_cb(data, cb);
+        }
+        self.precompute().smooth_streaming_cb(data, cb);
+    }
+}
+
+/// Builds the filter for `window_length`, `degree`, and `deriv` and smooths
+/// `data` with it, in one call.
+///
+/// ```
+/// let data: Vec<f64> = (0..8).map(|x| (x * x) as f64).collect();
+/// let slopes = savgol::savgol(&data, 5, 2, 1);
+/// assert!((slopes[3] - 6.0).abs() < 1e-10);
+/// ```
+///
+/// # Panics
+///
+/// If `window_length` is even, or the parameters are invalid as for
+/// `Filter::new`.
+pub fn savgol(data: &[f64], window_length: usize, degree: u64, deriv: u64) -> Vec<f64> {
+    assert!(window_length % 2 == 1, "window_length must be odd");
+    Filter::new_window(window_length, degree, deriv).smooth(data)
+}
+
+/// Convolves `data` with an odd-length `kernel` centered on each point,
+/// extending `data` past its ends according to `mode`, as `Filter::smooth_with`
+/// does with the filter's own kernel.
+///
+/// `kernel[j]` multiplies `data[k - radius + j]` for output `k`, with
+/// `radius = kernel.len() / 2`: the order of `Filter::coefficients`, which
+/// is the reverse of SciPy's default `savgol_coeffs` and this crate's
+/// `savgol_coeffs`. `EdgeMode::Interp` needs the polynomial fit, which a
+/// bare kernel does not carry, so with it the first and last `radius`
+/// outputs are NaN.
+///
+/// # Panics
+///
+/// If `kernel` has even length.
+pub fn apply_kernel(data: &[f64], kernel: &[f64], mode: EdgeMode) -> Vec<f64> {
+    assert!(kernel.len() % 2 == 1, "kernel length must be odd");
+    let radius = kernel.len() / 2;
+    if data.is_empty() {
+        return Vec::new();
+    }
+    if mode == EdgeMode::Interp {
+        let mut applied = vec![f64::NAN; data.len()];
+        for (k, window) in data.windows(kernel.len()).enumerate() {
+            applied[k + radius] = precomputed::dot(kernel, window);
+        }
+        return applied;
+    }
+    let padded: Vec<f64> = (-(radius as isize)..(data.len() + radius) as isize)
+        .map(|index| mode.extend(data, index))
+        .collect();
+    padded
+        .windows(kernel.len())
+        .map(|window| precomputed::dot(kernel, window))
+        .collect()
+}
+
+/// Applies `kernel` to the values of `window`, in double-double arithmetic,
+/// rounding only the result.
+#[cfg(feature = "high-precision")]
+fn dot_dd(kernel: &[DoubleDouble], window: impl Iterator<Item = f64>) -> f64 {
+    kernel
+        .iter()
+        .zip(window)
+        .fold(DoubleDouble::ZERO, |sum, (&weight, value)| {
+            sum + weight * value.into()
+        })
+        .to_f64()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::boxed::Box;
+    use alloc::format;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use approx::assert_relative_eq;
+
+    use crate::math;
+    use crate::{EdgeMode, FallbackPolicy, FilterBuilder, FilterError};
+
+    fn assert_float_eq(a: f64, b: f64) {
+        assert_relative_eq!(a, b, epsilon = 1e-10);
+    }
+
+    #[test]
+    fn smooth_empty_is_empty_for_every_order() {
+        for derivative in 0..=2 {
+            let filter = super::Filter::new(2, 2, derivative);
+            assert!(filter.smooth::<f64>(&[]).is_empty());
+            for mode in [EdgeMode::Mirror, EdgeMode::Wrap] {
+                assert!(filter.smooth_with::<f64>(&[], mode).is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn smooth_single_point_is_unchanged_and_derivatives_are_zero() {
+        assert_eq!(super::Filter::new(2, 2, 0).smooth(&[3.5]), vec![3.5]);
+        for derivative in 1..=2 {
+            let filter = super::Filter::new(2, 2, derivative);
+            assert_eq!(filter.smooth(&[3.5]), vec![0.0]);
+        }
+        let mirrored = crate::FilterBuilder::new()
+            .radius(2)
+            .derivative(1)
+            .edge_mode(EdgeMode::Mirror)
+            .build()
+            .unwrap();
+        assert_eq!(mirrored.smooth(&[3.5]), vec![0.0]);
+    }
+
+    #[test]
+    fn smooth_from_accepts_vec_array_box_and_slice() {
+        let filter = super::Filter::new(2, 2, 0);
+        let array = [1.0, -2.0, 3.0, -4.0, 5.0, -6.0, 7.0];
+        let expected = filter.smooth(&array);
+        let vec: Vec<f64> = array.into();
+        assert_eq!(filter.smooth_from(vec), expected);
+        assert_eq!(filter.smooth_from(array), expected);
+        assert_eq!(filter.smooth_from(&array[..]), expected);
+        let boxed: Box<[f64]> = array.into();
+        assert_eq!(filter.smooth_from(boxed), expected);
+    }
+
+    #[test]
+    fn smooth_window_on_wrapped_deque_matches_smooth_point() {
+        let filter = super::Filter::new_asymmetric(2, 3, 2, 1);
+        let mut window = alloc::collections::VecDeque::with_capacity(6);
+        for x in 0..10 {
+            if window.len() == 6 {
+                window.pop_front();
+            }
+            window.push_back((x as f64 * 0.7).sin() * 3.0);
+        }
+        assert!(!window.as_slices().1.is_empty());
+        let contiguous: Vec<f64> = window.iter().copied().collect();
+        for t in -2..=3 {
+            assert_float_eq(
+                filter.smooth_window(&window, t),
+                filter.smooth_point(t, &contiguous),
+            );
+        }
+    }
+
+    #[test]
+    fn smooth_angular_across_wrap_point() {
+        use core::f64::consts::PI;
+        // A steady rotation through 2π = 0, given in [0, 2π).
+        let angles: Vec<f64> = (0..20)
+            .map(|k| (5.5 + 0.1 * k as f64).rem_euclid(2.0 * PI))
+            .collect();
+        let filter = super::Filter::new(3, 2, 0);
+        let smoothed = filter.smooth_angular(&angles);
+
+        let wrap = |angle: f64| (angle + PI).rem_euclid(2.0 * PI) - PI;
+        // A symmetric kernel only scales a uniformly rotating unit vector,
+        // so interior angles are unchanged.
+        for k in 3..17 {
+            assert_float_eq(smoothed[k], wrap(angles[k]));
+        }
+        for (actual, expected) in smoothed.iter().zip(&angles) {
+            assert!((wrap(actual - expected)).abs() < 1e-2);
+            assert!(*actual > -PI && *actual <= PI);
+        }
+        // Smoothing the raw values is far off next to the jump.
+        let naive = filter.smooth(&angles);
+        assert!((0..20).any(|k| (naive[k] - angles[k]).abs() > 1.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn smooth_angular_rejects_derivatives() {
+        super::Filter::new(3, 2, 1).smooth_angular(&[0.0; 10]);
+    }
+
+    #[test]
+    fn smooth_array_matches_smooth() {
+        let data: [f64; 12] = core::array::from_fn(|x| (x as f64 * 0.6).sin() * 2.0 + x as f64);
+        let filters = [
+            super::Filter::new(2, 2, 0),
+            super::Filter::new(3, 3, 1),
+            super::Filter::new_asymmetric(1, 4, 2, 2),
+            super::Filter::new_window(6, 2, 0),
+            super::Filter::new(8, 2, 0),
+            super::Filter::new(2, 0, 0),
+            crate::FilterBuilder::new()
+                .radius(3)
+                .derivative(1)
+                .delta(0.5)
+                .edge_mode(EdgeMode::Mirror)
+                .build()
+                .unwrap(),
+        ];
+        for filter in filters {
+            let expected = filter.smooth(&data);
+            for (actual, expected) in filter.smooth_array(&data).iter().zip(&expected) {
+                assert_float_eq(*actual, *expected);
+            }
+        }
+        let filter = super::Filter::new(2, 2, 1);
+        assert_eq!(filter.smooth_array(&[]), [0.0; 0]);
+        assert_eq!(filter.smooth_array(&[2.0]), [0.0]);
+        assert_eq!(filter.smooth_array(&[2.0, 5.0]), [3.0, 3.0]);
+    }
+
+    #[test]
+    fn smooth_ints_matches_smooth_of_floats() {
+        let filter = super::Filter::new(2, 2, 1);
+        let ramp: Vec<i32> = (0..10).map(|x| 3 * x - 7).collect();
+        let floats: Vec<f64> = ramp.iter().map(|&x| x as f64).collect();
+        assert_eq!(filter.smooth_ints(&ramp), filter.smooth(&floats));
+        let readings: [u16; 6] = [100, 103, 99, 104, 110, 108];
+        assert_eq!(
+            filter.smooth_ints(&readings),
+            filter.smooth(&readings.map(f64::from))
+        );
+    }
+
+    #[test]
+    fn smooth_two_points_is_unchanged() {
+        let filter = super::Filter::new(1, 2, 0);
+        let smoothed = filter.smooth(&[1.0, 2.0]);
+        assert_eq!(smoothed, vec![1.0, 2.0]);
+    }
+
+    #[test]
+    fn smooth_two_points_first_derivative_is_divided_difference() {
+        let filter = super::Filter::new(1, 2, 1);
+        assert_eq!(filter.smooth(&[1.0, 5.0]), vec![4.0, 4.0]);
+    }
+
+    #[test]
+    fn smooth_two_points_second_derivative_is_zero() {
+        let filter = super::Filter::new(1, 2, 2);
+        assert_eq!(filter.smooth(&[1.0, 5.0]), vec![0.0, 0.0]);
+    }
+
+    #[test]
+    fn try_new_accepts_valid_parameters() {
+        assert!(super::Filter::try_new(1, 2, 2).is_ok());
+        assert!(super::Filter::try_new(2, 0, 0).is_ok());
+    }
+
+    #[test]
+    fn try_new_rejects_zero_radius() {
+        assert_eq!(
+            super::Filter::try_new(0, 0, 0).err(),
+            Some(FilterError::ZeroRadius)
+        );
+    }
+
+    #[test]
+    fn try_new_rejects_degree_exceeding_window() {
+        assert_eq!(
+            super::Filter::try_new(2, 5, 0).err(),
+            Some(FilterError::DegreeExceedsWindow {
+                degree: 5,
+                window_length: 5
+            })
+        );
+    }
+
+    #[test]
+    fn try_new_rejects_derivative_exceeding_degree() {
+        assert_eq!(
+            super::Filter::try_new(1, 0, 1).err(),
+            Some(FilterError::DerivativeExceedsDegree {
+                derivative: 1,
+                degree: 0
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_panics_on_invalid_parameters() {
+        super::Filter::new(1, 3, 0);
+    }
+
+    #[test]
+    fn smooth_three_points_derivatives_of_parabola() {
+        // The parabola through (-1, 1), (0, 5), (1, 3) is 5 + x - 3x^2.
+        let data = [1.0, 5.0, 3.0];
+        let first = super::Filter::new(10, 2, 1).smooth(&data);
+        assert_float_eq(first[0], 7.0);
+        assert_float_eq(first[1], 1.0);
+        assert_float_eq(first[2], -5.0);
+        for second in super::Filter::new(10, 2, 2).smooth(&data) {
+            assert_float_eq(second, -6.0);
+        }
+    }
+
+    #[test]
+    fn smooth_second_derivative_of_parabola() {
+        // y = 2 + 3x - 1.5x^2 sampled at x = k / 2 has y'' = -3 in x, and
+        // -3 / 2^2 = -0.75 per sample.
+        let data: Vec<f64> = (0..20)
+            .map(|k| {
+                let x = k as f64 * 0.5;
+                2.0 + 3.0 * x - 1.5 * x * x
+            })
+            .collect();
+        let per_sample = super::Filter::new(3, 2, 2).smooth(&data);
+        assert_all_float_eq(&per_sample, &[-0.75; 20]);
+        let per_x = FilterBuilder::new()
+            .radius(3)
+            .degree(3)
+            .derivative(2)
+            .delta(0.5)
+            .build()
+            .unwrap()
+            .smooth(&data);
+        assert_all_float_eq(&per_x, &[-3.0; 20]);
+        assert_all_float_eq(
+            &super::Filter::new(3, 2, 2).smooth_scaled(&data, 0.5),
+            &[-3.0; 20],
+        );
+    }
+
+    #[test]
+    fn second_and_third_derivatives_are_unscaled_by_factorials() {
+        // y = x^3 - 2x^2 + x has second derivative 6x - 4 and third 6.
+        let data: Vec<f64> = (0..20)
+            .map(|k| {
+                let x = k as f64;
+                x * x * x - 2.0 * x * x + x
+            })
+            .collect();
+        let second: Vec<f64> = (0..20).map(|k| 6.0 * k as f64 - 4.0).collect();
+        let third = vec![6.0; 20];
+        let positions: Vec<f64> = (0..20).map(|k| k as f64).collect();
+        let mut weights = vec![1.0; 20];
+        weights[7] = 2.0;
+        let array: [f64; 20] = data.clone().try_into().unwrap();
+        for (s, expected) in [(2, &second), (3, &third)] {
+            let filter = super::Filter::new(3, 3, s);
+            assert_all_float_eq(&filter.smooth(&data), expected);
+            assert_all_float_eq(&filter.precompute().smooth(&data), expected);
+            assert_all_float_eq(&filter.smooth_array(&array), expected);
+            assert_all_float_eq(&filter.interpolate(&data, &positions), expected);
+            assert_all_float_eq(&filter.smooth_xy(&positions, &data), expected);
+            assert_all_float_eq(&filter.smooth_weighted(&data, &weights), expected);
+            #[cfg(feature = "high-precision")]
+            assert_all_float_eq(&filter.smooth_high_precision(&data), expected);
+            let derivatives = super::Filter::new(3, 3, 0).smooth_derivatives(&data, &[s]);
+            assert_all_float_eq(&derivatives[0], expected);
+        }
+        // `fit` gives Taylor coefficients instead, smaller by s!.
+        let fit = super::Filter::new(3, 3, 0).fit(&data);
+        assert_float_eq(fit[5][2], second[5] / 2.0);
+        assert_float_eq(fit[5][3], 6.0 / 6.0);
+    }
+
+    #[test]
+    fn smooth_5pt_quadratic_on_7pts_linear() {
+        let filter = super::Filter::new(2, 2, 0);
+        let data = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0];
+        let smoothed = filter.smooth(data.as_slice());
+        assert_eq!(smoothed.len(), 7);
+        assert_float_eq(smoothed[0], 1.0);
+        assert_float_eq(smoothed[1], 2.0);
+        assert_float_eq(smoothed[2], 3.0);
+        assert_float_eq(smoothed[3], 4.0);
+        assert_float_eq(smoothed[4], 5.0);
+        assert_float_eq(smoothed[5], 6.0);
+        assert_float_eq(smoothed[6], 7.0);
+    }
+
+    #[test]
+    fn smooth_5pt_quadratic_on_7pts_nonlinear() {
+        let filter = super::Filter::new(2, 2, 0);
+        let data = vec![1.0, -2.0, 3.0, -4.0, 5.0, -6.0, 7.0];
+        let smoothed = filter.smooth(data.as_slice());
+        assert_eq!(smoothed.len(), 7);
+        assert_float_eq(smoothed[0], 1.1142857142857143);
+        assert_float_eq(smoothed[1], -0.8571428571428571);
+        assert_float_eq(smoothed[2], -1.1142857142857143);
+        assert_float_eq(smoothed[3], 1.4857142857142858);
+        assert_float_eq(smoothed[4], -1.8571428571428572);
+        assert_float_eq(smoothed[5], 0.17142857142857143);
+        assert_float_eq(smoothed[6], 5.057142857142857);
+    }
+
+    #[test]
+    fn smooth_5pt_quadratic_on_5pts_nonlinear_radius_too_large() {
+        let filter = super::Filter::new(20, 2, 0);
+        let data = vec![1.0, -2.0, 3.0, -4.0, 5.0];
+        let smoothed = filter.smooth(data.as_slice());
+        assert_eq!(smoothed.len(), 5);
+        assert_float_eq(smoothed[0], 1.1142857142857143);
+        assert_float_eq(smoothed[1], -0.8571428571428571);
+        assert_float_eq(smoothed[2], -1.1142857142857143);
+        assert_float_eq(smoothed[3], 0.34285714285714286);
+        assert_float_eq(smoothed[4], 3.5142857142857142);
+    }
+
+    #[test]
+    fn smooth_5pt_quadratic_on_6pts_nonlinear_radius_too_large() {
+        let filter = super::Filter::new(20, 2, 0);
+        let data = vec![1.0, -2.0, 3.0, -4.0, 5.0, -6.0];
+        let smoothed = filter.smooth(data.as_slice());
+        assert_eq!(smoothed.len(), 6);
+        assert_float_eq(smoothed[0], 1.1142857142857143);
+        assert_float_eq(smoothed[1], -0.8571428571428571);
+        assert_float_eq(smoothed[2], -1.1142857142857143);
+        assert_float_eq(smoothed[3], 1.4857142857142858);
+        assert_float_eq(smoothed[4], -0.2571428571428571);
+        assert_float_eq(smoothed[5], -4.285714285714286);
+    }
+
+    #[test]
+    fn smooth_streaming_cb_matches_smooth() {
+        let filter = super::Filter::new(3, 2, 0);
+        let data: Vec<f64> = (0..40).map(|x| (x as f64 * 0.3).sin() + x as f64).collect();
+        let mut streamed = Vec::new();
+        filter.smooth_streaming_cb(&data, |index, value| streamed.push((index, value)));
+        let smoothed = filter.smooth(&data);
+        assert_eq!(streamed.len(), smoothed.len());
+        for (expected_index, ((index, value), expected)) in
+            streamed.iter().zip(smoothed.iter()).enumerate()
+        {
+            assert_eq!(*index, expected_index);
+            assert_float_eq(*value, *expected);
+        }
+    }
+
+    #[test]
+    fn best_degree_picks_cubic_for_cubic_data() {
+        let data: Vec<f64> = (0..60)
+            .map(|k| {
+                let x = k as f64 / 10.0 - 3.0;
+                let noise = 0.01 * ((k * 37 % 11) as f64 - 5.0);
+                x * x * x - 2.0 * x + noise
+            })
+            .collect();
+        assert_eq!(super::Filter::best_degree(&data, 5, &[1, 3]), 3);
+        assert_eq!(super::Filter::best_degree(&data, 5, &[3, 1]), 3);
+        // Degree 10 interpolates the 11-point window and is skipped.
+        assert_eq!(super::Filter::best_degree(&data, 5, &[1, 10]), 1);
+    }
+
+    #[test]
+    fn smooth_by_field_matches_extracted() {
+        struct Reading {
+            t: f64,
+            value: f64,
+        }
+        let readings: Vec<Reading> = (0..12)
+            .map(|k| Reading {
+                t: k as f64 * 0.1,
+                value: (k * k % 7) as f64,
+            })
+            .collect();
+        let filter = super::Filter::new(2, 2, 0);
+        let values: Vec<f64> = readings.iter().map(|reading| reading.value).collect();
+        assert_eq!(
+            filter.smooth_by(&readings, |reading| reading.value),
+            filter.smooth(&values)
+        );
+        let times: Vec<f64> = readings.iter().map(|reading| reading.t).collect();
+        assert_all_float_eq(&filter.smooth_by(&readings, |reading| reading.t), &times);
+    }
+
+    #[test]
+    fn condition_number_estimate_grows_with_degree() {
+        let estimates: Vec<f64> = (0..=8)
+            .map(|degree| super::Filter::new(6, degree, 0).condition_number_estimate())
+            .collect();
+        assert_float_eq(estimates[0], 1.0);
+        assert!(estimates.windows(2).all(|pair| pair[1] > pair[0]));
+        assert_float_eq(estimates[4], crate::condition_number(6, 4));
+    }
+
+    #[test]
+    fn smooth_separable_2d_keeps_constant_and_ramp() {
+        let filter = super::Filter::new(2, 2, 0);
+        let (width, height) = (9, 7);
+        let constant = vec![3.5; width * height];
+        assert_all_float_eq(
+            &filter.smooth_separable_2d(&constant, width, height),
+            &constant,
+        );
+        let ramp: Vec<f64> = (0..width * height)
+            .map(|k| 2.0 * (k % width) as f64 - 0.5 * (k / width) as f64 + 1.0)
+            .collect();
+        assert_all_float_eq(&filter.smooth_separable_2d(&ramp, width, height), &ramp);
+        // The mixed derivative of x * y is 1.
+        let product: Vec<f64> = (0..width * height)
+            .map(|k| ((k % width) * (k / width)) as f64)
+            .collect();
+        let mixed = super::Filter::new(2, 2, 1).smooth_separable_2d(&product, width, height);
+        assert_all_float_eq(&mixed, &vec![1.0; width * height]);
+    }
+
+    #[test]
+    fn smooth_nan_with_counts_drops_near_gaps() {
+        let filter = super::Filter::new(2, 2, 0);
+        let mut data: Vec<f64> = (0..20).map(|x| x as f64).collect();
+        data[10] = f64::NAN;
+        data[14..18].fill(f64::NAN);
+        let (smoothed, counts) = filter.smooth_nan_with_counts(&data);
+        assert_eq!(smoothed.len(), counts.len());
+        assert_eq!(&counts[..8], &[5; 8]);
+        assert_eq!(&counts[8..13], &[4, 4, 4, 4, 3]);
+        // Windows centered on 15 and 16 keep only 1 and 2 samples.
+        assert_eq!(&counts[15..17], &[0, 0]);
+        assert!(smoothed[15].is_nan() && smoothed[16].is_nan());
+        for (value, count) in smoothed.iter().zip(&counts) {
+            assert_eq!(value.is_nan(), *count == 0);
+        }
+    }
+
+    #[test]
+    fn display_shows_parameters_and_kernel() {
+        let filter = super::Filter::new(2, 2, 0);
+        let shown = format!("{}", filter);
+        assert_eq!(
+            shown,
+            "SavGol(window=5, degree=2, deriv=0) kernel=[-0.086, 0.343, 0.486, 0.343, -0.086]"
+        );
+        assert!(shown.contains("window=5"));
+        assert!(format!("{:.5}", filter).contains("0.48571"));
+    }
+
+    #[test]
+    fn savgol_matches_filter() {
+        let data: Vec<f64> = (0..15).map(|x| (x as f64 * 0.8).sin()).collect();
+        assert_eq!(
+            super::savgol(&data, 7, 3, 0),
+            super::Filter::new(3, 3, 0).smooth(&data)
+        );
+        assert_eq!(
+            super::savgol(&data, 5, 2, 1),
+            super::Filter::new(2, 2, 1).smooth(&data)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "window_length must be odd")]
+    fn savgol_rejects_even_window() {
+        super::savgol(&[1.0, 2.0, 3.0, 4.0, 5.0], 4, 2, 0);
+    }
+
+    #[test]
+    fn from_window_length_accepts_only_odd_lengths() {
+        let filter = super::Filter::from_window_length(7, 2, 0).unwrap();
+        assert_eq!(filter, super::Filter::new(3, 2, 0));
+        assert_eq!(filter.window_length(), 7);
+        assert_eq!(filter.radius(), 3);
+        assert_eq!(
+            super::Filter::from_window_length(6, 2, 0),
+            Err(FilterError::EvenWindowLength { window_length: 6 })
+        );
+        assert_eq!(
+            super::Filter::from_window_length(0, 0, 0),
+            Err(FilterError::EvenWindowLength { window_length: 0 })
+        );
+        assert_eq!(
+            super::Filter::from_window_length(1, 0, 0),
+            Err(FilterError::ZeroRadius)
+        );
+        assert_eq!(super::Filter::new_window(6, 2, 0).window_length(), 6);
+    }
+
+    #[test]
+    fn iter_smoothed_matches_smooth() {
+        let data: Vec<f64> = (0..30).map(|x| (x as f64 * 0.6).sin() * x as f64).collect();
+        let filter = super::Filter::new(3, 2, 1);
+        let smoothed = filter.smooth(&data);
+        let first: Vec<f64> = filter.iter_smoothed(&data).take(3).collect();
+        assert_eq!(first, smoothed[..3]);
+        assert_eq!(filter.iter_smoothed(&data).collect::<Vec<_>>(), smoothed);
+        let mirrored = FilterBuilder::new()
+            .radius(3)
+            .edge_mode(EdgeMode::Mirror)
+            .build()
+            .unwrap();
+        assert_eq!(
+            mirrored.iter_smoothed(&data).collect::<Vec<_>>(),
+            mirrored.smooth(&data)
+        );
+        for len in [0, 1, 2, 5] {
+            assert_eq!(
+                filter.iter_smoothed(&data[..len]).collect::<Vec<_>>(),
+                filter.smooth(&data[..len])
+            );
+        }
+    }
+
+    #[test]
+    fn iter_smoothed_matches_smooth_on_short_inputs_in_every_mode() {
+        let data = [3.0, -1.0, 4.0];
+        for mode in [
+            EdgeMode::Interp,
+            EdgeMode::Mirror,
+            EdgeMode::Nearest,
+            EdgeMode::Constant(7.5),
+            EdgeMode::Wrap,
+        ] {
+            let filter = FilterBuilder::new()
+                .radius(2)
+                .edge_mode(mode)
+                .build()
+                .unwrap();
+            for len in 0..=3 {
+                assert_eq!(
+                    filter.iter_smoothed(&data[..len]).collect::<Vec<_>>(),
+                    filter.smooth(&data[..len]),
+                    "{:?} with {} samples",
+                    mode,
+                    len
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "sigma must be positive")]
+    fn smooth_gaussian_weighted_rejects_zero_sigma() {
+        super::Filter::new(2, 2, 0).smooth_gaussian_weighted(&[1.0, 4.0, 2.0, 8.0, 5.0], 0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "sigma must be positive")]
+    fn smooth_gaussian_weighted_rejects_nan_sigma() {
+        super::Filter::new(2, 2, 0).smooth_gaussian_weighted(&[1.0, 4.0, 2.0, 8.0, 5.0], f64::NAN);
+    }
+
+    #[test]
+    fn smooth_gaussian_weighted_infinite_sigma_is_smooth() {
+        let filter = super::Filter::new(2, 2, 0);
+        let data = [1.0, 4.0, 2.0, 8.0, 5.0, 7.0];
+        assert_all_float_eq(
+            &filter.smooth_gaussian_weighted(&data, f64::INFINITY),
+            &filter.smooth(&data),
+        );
+    }
+
+    #[test]
+    fn smooth_gaussian_weighted_large_sigma_is_smooth() {
+        let data: Vec<f64> = (0..25)
+            .map(|x| (x as f64 * 0.9).sin() + x as f64 * 0.1)
+            .collect();
+        for filter in [super::Filter::new(3, 2, 0), super::Filter::new(4, 3, 1)] {
+            assert_all_float_eq(
+                &filter.smooth_gaussian_weighted(&data, 1e8),
+                &filter.smooth(&data),
+            );
+            assert_all_float_eq(
+                &filter.smooth_gaussian_weighted(&data[..5], 1e8),
+                &filter.smooth(&data[..5]),
+            );
+        }
+    }
+
+    #[test]
+    fn smooth_gaussian_weighted_keeps_polynomials() {
+        let data: Vec<f64> = (0..20).map(|x| (x * x) as f64 - 3.0 * x as f64).collect();
+        let filter = super::Filter::new(4, 2, 0);
+        assert_all_float_eq(&filter.smooth_gaussian_weighted(&data, 1.5), &data);
+        // A narrow taper follows the data more closely than the flat window.
+        let noisy: Vec<f64> = (0..20)
+            .map(|x| if x % 2 == 0 { 1.0 } else { -1.0 })
+            .collect();
+        let flat = filter.smooth(&noisy);
+        let narrow = filter.smooth_gaussian_weighted(&noisy, 0.5);
+        assert!(narrow[10].abs() > flat[10].abs());
+    }
+
+    #[test]
+    fn apply_kernel_reproduces_smooth() {
+        let data: Vec<f64> = (0..20).map(|x| (x as f64 * 0.4).cos() * x as f64).collect();
+        let filter = super::Filter::new(3, 2, 0);
+        let applied = super::apply_kernel(&data, &filter.coefficients(), EdgeMode::Interp);
+        let smoothed = filter.smooth(&data);
+        assert_all_float_eq(&applied[3..17], &smoothed[3..17]);
+        assert!(applied[..3]
+            .iter()
+            .chain(&applied[17..])
+            .all(|x| x.is_nan()));
+        for mode in [EdgeMode::Mirror, EdgeMode::Nearest, EdgeMode::Wrap] {
+            assert_all_float_eq(
+                &super::apply_kernel(&data, &filter.coefficients(), mode),
+                &filter.smooth_with(&data, mode),
+            );
+        }
+    }
+
+    #[test]
+    fn presets_match_new() {
+        assert_eq!(super::Filter::default(), super::Filter::new(2, 2, 0));
+        assert_eq!(super::Filter::savgol_5_2(), super::Filter::new(2, 2, 0));
+        assert_eq!(super::Filter::savgol_7_2(), super::Filter::new(3, 2, 0));
+        assert_eq!(
+            super::Filter::first_derivative(4, 3),
+            super::Filter::new(4, 3, 1)
+        );
+    }
+
+    #[test]
+    fn huge_radius_is_rejected() {
+        assert_eq!(
+            super::Filter::try_new(usize::MAX / 2 + 1, 2, 0),
+            Err(FilterError::RadiusTooLarge)
+        );
+        assert_eq!(
+            super::Filter::try_new(usize::MAX / 4, 2, 0),
+            Err(FilterError::RadiusTooLarge)
+        );
+        assert_eq!(
+            super::Filter::try_new_asymmetric(usize::MAX, 1, 2, 0),
+            Err(FilterError::RadiusTooLarge)
+        );
+        assert_eq!(
+            super::Filter::try_new_window(usize::MAX, 2, 0),
+            Err(FilterError::RadiusTooLarge)
+        );
+        assert_eq!(
+            FilterBuilder::new().radius(usize::MAX).build(),
+            Err(FilterError::RadiusTooLarge)
+        );
+        assert_eq!(
+            FilterBuilder::new().radius(usize::MAX / 2).build(),
+            Err(FilterError::RadiusTooLarge)
+        );
+        assert!(FilterBuilder::new()
+            .radius(usize::MAX)
+            .radius(3)
+            .build()
+            .is_ok());
+        assert!(super::Filter::try_new(1000, 2, 0).is_ok());
+    }
+
+    #[test]
+    fn huge_degree_is_rejected() {
+        assert_eq!(
+            super::Filter::try_new(2, u64::MAX, 0),
+            Err(FilterError::DegreeTooLarge { degree: u64::MAX })
+        );
+        assert_eq!(
+            super::Filter::try_new(2, u64::MAX, u64::MAX),
+            Err(FilterError::DegreeTooLarge { degree: u64::MAX })
+        );
+        assert_eq!(
+            super::Filter::try_new_window(usize::MAX / 8, 1 << 40, 0),
+            Err(FilterError::DegreeTooLarge { degree: 1 << 40 })
+        );
+        assert_eq!(
+            super::Filter::try_new(2, 2, u64::MAX),
+            Err(FilterError::DerivativeExceedsDegree {
+                derivative: u64::MAX,
+                degree: 2
+            })
+        );
+    }
+
+    #[test]
+    fn short_input_lowers_degree() {
+        let filter = super::Filter::new(10, 6, 0);
+        let data: [f64; 5] = [1.0, 4.0, 2.0, 8.0, 5.0];
+        let smoothed = filter.smooth(&data);
+        assert!(smoothed.iter().all(|x| x.is_finite()));
+        assert_all_float_eq(&smoothed, &super::Filter::new(2, 3, 0).smooth(&data));
+        assert!(smoothed.iter().zip(&data).any(|(s, x)| (s - x).abs() > 0.1));
+        // A derivative filter keeps enough degree for its derivative.
+        let second = super::Filter::new(10, 2, 2);
+        let parabola: [f64; 3] = [0.0, 1.0, 4.0];
+        assert_all_float_eq(&second.smooth(&parabola), &[2.0, 2.0, 2.0]);
+    }
+
+    #[test]
+    fn fallback_policy_shrink_is_default() {
+        let filter = super::Filter::new(10, 2, 0);
+        assert_eq!(filter.fallback_policy(), FallbackPolicy::Shrink);
+        let data = [1.0, 4.0, 2.0];
+        assert_all_float_eq(&filter.try_smooth(&data).unwrap(), &filter.smooth(&data));
+        assert_all_float_eq(
+            &filter.smooth(&data),
+            &super::Filter::new(1, 2, 0).smooth(&data),
+        );
+    }
+
+    #[test]
+    fn fallback_policy_error_rejects_short_input() {
+        let filter = FilterBuilder::new()
+            .radius(10)
+            .fallback_policy(FallbackPolicy::Error)
+            .build()
+            .unwrap();
+        assert_eq!(
+            filter.try_smooth(&[1.0, 4.0, 2.0]),
+            Err(FilterError::InputTooShort {
+                len: 3,
+                min_len: 21
+            })
+        );
+        let data: Vec<f64> = (0..21).map(|x| x as f64).collect();
+        assert_all_float_eq(&filter.try_smooth(&data).unwrap(), &data);
+    }
+
+    #[test]
+    #[should_panic(expected = "input of 3 samples")]
+    fn fallback_policy_error_panics_in_smooth() {
+        let filter = FilterBuilder::new()
+            .radius(10)
+            .fallback_policy(FallbackPolicy::Error)
+            .build()
+            .unwrap();
+        filter.smooth(&[1.0, 4.0, 2.0]);
+    }
+
+    #[test]
+    fn fallback_policy_shrink_to_min() {
+        let data = [1.0, 4.0, 2.0];
+        let builder = FilterBuilder::new().radius(10);
+        let floor_1 = builder
+            .fallback_policy(FallbackPolicy::ShrinkToMin(1))
+            .build()
+            .unwrap();
+        assert_all_float_eq(
+            &floor_1.try_smooth(&data).unwrap(),
+            &super::Filter::new(1, 2, 0).smooth(&data),
+        );
+        let floor_2 = builder
+            .fallback_policy(FallbackPolicy::ShrinkToMin(2))
+            .build()
+            .unwrap();
+        assert_eq!(
+            floor_2.try_smooth(&data),
+            Err(FilterError::InputTooShort { len: 3, min_len: 5 })
+        );
+        let floor_huge = builder
+            .fallback_policy(FallbackPolicy::ShrinkToMin(usize::MAX))
+            .build()
+            .unwrap();
+        assert_eq!(
+            floor_huge.try_smooth(&data),
+            Err(FilterError::InputTooShort {
+                len: 3,
+                min_len: 21
+            })
+        );
+    }
+
+    #[test]
+    fn smooth_streaming_cb_short_input() {
+        let filter = super::Filter::new(20, 2, 0);
+        let data = vec![1.0, -2.0, 3.0, -4.0, 5.0];
+        let mut streamed = Vec::new();
+        filter.smooth_streaming_cb(&data, |_, value| streamed.push(value));
+        assert_eq!(streamed, filter.smooth(&data));
+    }
+
+    #[test]
+    fn attenuation_at_dc_is_unity_for_smoother() {
+        let filter = super::Filter::new(3, 2, 0);
+        assert_float_eq(filter.attenuation_at(0.0), 1.0);
+    }
+
+    #[test]
+    fn attenuation_at_high_frequency_is_below_unity() {
+        let filter = super::Filter::new(3, 2, 0);
+        for freq in [0.2, 0.3, 0.4, 0.5] {
+            assert!(filter.attenuation_at(freq) < 1.0);
+        }
+    }
+
+    #[test]
+    fn residual_std_of_polynomial_is_zero() {
+        let filter = super::Filter::new(3, 2, 0);
+        let data: Vec<f64> = (0..20).map(|x| (x * x) as f64 - 3.0 * x as f64).collect();
+        assert!(filter.residual_std(&data) < 1e-10);
+    }
+
+    #[test]
+    fn residual_std_recovers_noise_level() {
+        // A slow sine plus uniform noise on [-0.5, 0.5), whose std is
+        // 1 / sqrt(12), from a fixed linear congruential generator.
+        let mut state: u64 = 12345;
+        let data: Vec<f64> = (0..4000)
+            .map(|x| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                let noise = (state >> 11) as f64 / (1u64 << 53) as f64 - 0.5;
+                (x as f64 * 0.01).sin() + noise
+            })
+            .collect();
+        let expected = 1.0 / 12f64.sqrt();
+        let estimate = super::Filter::new(6, 2, 0).residual_std(&data);
+        assert!((estimate - expected).abs() < 0.05 * expected);
+    }
+
+    #[test]
+    fn residual_std_without_degrees_of_freedom_is_nan() {
+        let filter = super::Filter::new(2, 4, 0);
+        assert!(filter.residual_std(&[1.0, 4.0, 2.0, 8.0, 5.0]).is_nan());
+    }
+
+    #[test]
+    fn smooth_robust_ignores_single_spike() {
+        let filter = super::Filter::new(4, 2, 0);
+        let clean: Vec<f64> = (0..40)
+            .map(|x| (x as f64 * 0.2).sin() + 0.05 * ((x * 7919 % 13) as f64 / 13.0 - 0.5))
+            .collect();
+        let mut spiked = clean.clone();
+        spiked[20] += 50.0;
+
+        let plain = filter.smooth(&spiked);
+        let reference = filter.smooth(&clean);
+        assert!((plain[20] - reference[20]).abs() > 10.0);
+
+        let robust = filter.smooth_robust(&spiked, 3);
+        for (actual, expected) in robust.iter().zip(&reference) {
+            assert!((actual - expected).abs() < 0.05);
+        }
+    }
+
+    #[test]
+    fn smooth_robust_without_iterations_is_smooth() {
+        let filter = super::Filter::new(2, 2, 0);
+        let data = [1.0, -2.0, 3.0, -4.0, 5.0, -6.0, 7.0];
+        let robust = filter.smooth_robust(&data, 0);
+        for (actual, expected) in robust.iter().zip(filter.smooth(&data)) {
+            assert_float_eq(*actual, expected);
+        }
+    }
+
+    #[test]
+    fn degree_0_kernel_matches_gram_polynomials() {
+        for (radius_left, radius_right) in [(1, 1), (3, 3), (2, 5)] {
+            let filter = super::Filter::new_asymmetric(radius_left, radius_right, 0, 0);
+            let shift = radius_right as i64 - radius_left as i64;
+            for t in -(radius_left as i64)..=radius_right as i64 {
+                for (i, weight) in (-(radius_left as i64)..).zip(filter.kernel(t)) {
+                    let general = math::weights_doubled(
+                        2 * i - shift,
+                        (radius_left + radius_right) as i64,
+                        0,
+                        2 * t - shift,
+                        0,
+                    );
+                    assert_float_eq(weight, general);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn degree_0_smooth_is_window_mean() {
+        let radius = 3;
+        let data: Vec<f64> = (0..20)
+            .map(|x| 4.0 + 0.3 * ((x * 7919 % 13) as f64 / 13.0 - 0.5))
+            .collect();
+        let smoothed = super::Filter::new(radius, 0, 0).smooth(&data);
+        let mean = |window: &[f64]| window.iter().sum::<f64>() / window.len() as f64;
+        for k in radius..data.len() - radius {
+            assert_float_eq(smoothed[k], mean(&data[k - radius..=k + radius]));
+        }
+        // The edges are fit to the first and last full windows.
+        for &value in &smoothed[..radius] {
+            assert_float_eq(value, mean(&data[..2 * radius + 1]));
+        }
+        for &value in &smoothed[data.len() - radius..] {
+            assert_float_eq(value, mean(&data[data.len() - 2 * radius - 1..]));
+        }
+    }
+
+    #[test]
+    fn kernel_sums_to_one_for_smoothing_and_zero_for_derivatives() {
+        for radius in [1, 2, 5, 12] {
+            for degree in 0..=(2 * radius as u64).min(8) {
+                for derivative in 0..=degree {
+                    let filter = super::Filter::new(radius, degree, derivative);
+                    let expected = if derivative == 0 { 1.0 } else { 0.0 };
+                    assert_relative_eq!(filter.kernel_sum(), expected, epsilon = 1e-9);
+                    assert!(filter.verify_normalization());
+                }
+            }
+        }
+        assert!(super::Filter::new_window(6, 3, 1).verify_normalization());
+    }
+
+    #[test]
+    fn smooth_with_variance_uses_squared_weights_per_point() {
+        let filter = super::Filter::new(3, 2, 1);
+        let data: Vec<f64> = (0..15).map(|x| (x as f64 * 0.5).sin()).collect();
+        let noise_var = 0.25;
+        let result = filter.smooth_with_variance(&data, noise_var);
+
+        let squares = |weights: &[f64]| weights.iter().map(|w| w * w).sum::<f64>();
+        let interior = squares(&filter.coefficients());
+        for &(_, variance) in &result[3..12] {
+            assert_float_eq(variance, noise_var * interior);
+        }
+        let precomputed = filter.precompute();
+        assert_float_eq(result[0].1, noise_var * squares(&precomputed.head[0]));
+        assert_float_eq(result[14].1, noise_var * squares(&precomputed.tail[2]));
+        assert!(result[0].1 > result[7].1);
+        for ((value, _), expected) in result.iter().zip(filter.smooth(&data)) {
+            assert_float_eq(*value, expected);
+        }
+    }
+
+    #[test]
+    fn smooth_chunks_matches_smooth_on_concatenation() {
+        let filter = super::Filter::new(2, 2, 0);
+        let data: Vec<f64> = (0..40).map(|x| (x as f64 * 0.45).sin() * 3.0).collect();
+        let chunks = data.chunks(7).map(<[f64]>::to_vec);
+        let outputs: Vec<Vec<f64>> = filter.smooth_chunks(chunks).collect();
+        assert_eq!(outputs.len(), 7);
+        assert_eq!(outputs[0].len(), 5);
+        let joined: Vec<f64> = outputs.concat();
+        assert_eq!(joined.len(), data.len());
+        for (actual, expected) in joined.iter().zip(filter.smooth(&data)) {
+            assert_float_eq(*actual, expected);
+        }
+    }
+
+    #[test]
+    fn smooth_chunks_shorter_than_window() {
+        let filter = super::Filter::new(3, 2, 0);
+        let data = vec![1.0, 4.0, 2.0];
+        let chunks = vec![vec![1.0], vec![4.0, 2.0]];
+        let joined: Vec<f64> = filter.smooth_chunks(chunks.into_iter()).flatten().collect();
+        assert_eq!(joined, filter.smooth(&data));
+        assert_eq!(filter.smooth_chunks(core::iter::empty()).count(), 0);
+    }
+
+    #[test]
+    fn smooth_adaptive_preserves_peak_and_smooths_flat_region() {
+        // A narrow peak on a flat baseline, plus uniform noise from a fixed
+        // linear congruential generator.
+        let mut state: u64 = 7;
+        let clean: Vec<f64> = (0..200)
+            .map(|x| 10.0 * (-((x as f64 - 100.0) / 3.0).powi(2)).exp())
+            .collect();
+        let data: Vec<f64> = clean
+            .iter()
+            .map(|y| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                y + 0.2 * ((state >> 11) as f64 / (1u64 << 53) as f64 - 0.5)
+            })
+            .collect();
+        let filter = super::Filter::new(2, 2, 0);
+        let adaptive = filter.smooth_adaptive(&data, 2, 15);
+        let wide = super::Filter::new(15, 2, 0).smooth(&data);
+        let narrow = filter.smooth(&data);
+
+        assert!((adaptive[100] - clean[100]).abs() < 0.5);
+        assert!((wide[100] - clean[100]).abs() > 2.0);
+
+        let flat_error =
+            |smoothed: &[f64]| -> f64 { smoothed[20..60].iter().map(|y| y * y).sum::<f64>() };
+        assert!(flat_error(&adaptive) < 0.5 * flat_error(&narrow));
+    }
+
+    #[test]
+    fn edge_coefficients_are_the_rows_smooth_uses() {
+        let filter = super::Filter::new(3, 2, 0);
+        assert_eq!(filter.edge_coefficients(0), filter.coefficients());
+        let precomputed = filter.precompute();
+        for t in 1..=3 {
+            assert_eq!(
+                filter.edge_coefficients(-t),
+                precomputed.head[3 - t as usize]
+            );
+            assert_eq!(
+                filter.edge_coefficients(t),
+                precomputed.tail[t as usize - 1]
+            );
+            assert_ne!(filter.edge_coefficients(t), filter.coefficients());
+        }
+        // The edge fit still reproduces constants.
+        let sum: f64 = filter.edge_coefficients(-3).iter().sum();
+        assert_float_eq(sum, 1.0);
+    }
+
+    #[test]
+    fn is_smoothing_is_false_when_fit_interpolates() {
+        for (radius, degree, smoothing) in [
+            (1, 1, true),
+            (1, 2, false),
+            (2, 3, true),
+            (2, 4, false),
+            (5, 9, true),
+            (5, 10, false),
+        ] {
+            assert_eq!(
+                super::Filter::new(radius, degree, 0).is_smoothing(),
+                smoothing
+            );
+        }
+        assert!(!super::Filter::new_asymmetric(1, 2, 3, 0).is_smoothing());
+        assert!(super::Filter::new_window(6, 4, 0).is_smoothing());
+        assert!(!super::Filter::new_window(6, 5, 0).is_smoothing());
+
+        // An interpolating filter reproduces its input.
+        let data = [1.0, -2.0, 3.0, -4.0, 5.0, -6.0, 7.0];
+        let filter = super::Filter::new(2, 4, 0);
+        assert_all_float_eq(&filter.smooth(&data), &data);
+    }
+
+    #[cfg(feature = "high-precision")]
+    #[test]
+    fn smooth_high_precision_reproduces_degree_10_polynomial() {
+        // x^10 - 3x^7 + 5x^2 at x = -25..=25 is exactly representable, and a
+        // degree 10 fit over the 51-point window must reproduce it exactly.
+        let data: Vec<f64> = (-25..=25)
+            .map(|x: i64| (x.pow(10) - 3 * x.pow(7) + 5 * x.pow(2)) as f64)
+            .collect();
+        let filter = super::Filter::new(25, 10, 0);
+        let max_error = |smoothed: Vec<f64>| {
+            smoothed
+                .iter()
+                .zip(&data)
+                .map(|(y, x)| (y - x).abs())
+                .fold(0.0, f64::max)
+        };
+        let f64_error = max_error(filter.smooth(&data));
+        let high_precision_error = max_error(filter.smooth_high_precision(&data));
+        // About 1.8 in f64, against values up to 1e14.
+        assert!(high_precision_error < 1e-6);
+        assert!(high_precision_error * 1e3 < f64_error);
+
+        let mirrored = filter.with_options(1.0, EdgeMode::Mirror);
+        for (actual, expected) in mirrored
+            .smooth_high_precision(&data)
+            .iter()
+            .zip(mirrored.smooth(&data))
+        {
+            assert!((actual - expected).abs() < 1e-9 * data[0]);
+        }
+    }
+
+    #[test]
+    fn matrix_times_data_is_smooth() {
+        let filter = super::Filter::new(2, 2, 0);
+        let matrix = filter.matrix(7);
+        assert_eq!(matrix.len(), 7);
+        let multiply = |data: &[f64]| -> Vec<f64> {
+            matrix
+                .iter()
+                .map(|row| row.iter().zip(data).map(|(m, x)| m * x).sum())
+                .collect()
+        };
+        let ramp: Vec<f64> = (0..7).map(|x| x as f64).collect();
+        assert_all_float_eq(&multiply(&ramp), &filter.smooth(&ramp));
+        let data = [1.0, -2.0, 3.0, -4.0, 5.0, -6.0, 7.0];
+        assert_all_float_eq(&multiply(&data), &filter.smooth(&data));
+        assert_all_float_eq(
+            &matrix[3],
+            &[
+                0.0,
+                -3.0 / 35.0,
+                12.0 / 35.0,
+                17.0 / 35.0,
+                12.0 / 35.0,
+                -3.0 / 35.0,
+                0.0,
+            ],
+        );
+    }
 
-pub struct Filter {
-    radius: usize,   // m
-    degree: u64,     // n
-    derivative: u64, // s
-}
+    #[test]
+    fn matrix_uses_interp_edges() {
+        let data: Vec<f64> = (0..12).map(|x| (x as f64 * 1.3).cos() * x as f64).collect();
+        for mode in [EdgeMode::Mirror, EdgeMode::Wrap] {
+            let filter = FilterBuilder::new()
+                .radius(2)
+                .edge_mode(mode)
+                .build()
+                .unwrap();
+            let product: Vec<f64> = filter
+                .matrix(data.len())
+                .iter()
+                .map(|row| row.iter().zip(&data).map(|(m, x)| m * x).sum())
+                .collect();
+            assert_all_float_eq(&product, &filter.smooth_with(&data, EdgeMode::Interp));
+        }
+    }
 
-impl Filter {
-    pub fn new(radius: usize, degree: u64, derivative: u64) -> Self {
-        Filter {
-            radius,
-            degree,
-            derivative,
+    #[test]
+    fn frequency_response_dc_gain() {
+        let response = super::Filter::new(3, 2, 0).frequency_response(11);
+        assert_eq!(response.len(), 11);
+        assert_float_eq(response[0].0, 0.0);
+        assert_float_eq(response[0].1, 1.0);
+        assert_float_eq(response[10].0, 0.5);
+
+        let derivative = super::Filter::new(3, 2, 1).frequency_response(11);
+        assert_float_eq(derivative[0].1, 0.0);
+    }
+
+    #[test]
+    fn frequency_response_matches_attenuation_at() {
+        let filter = super::Filter::new(4, 3, 0);
+        for (freq, magnitude) in filter.frequency_response(6) {
+            assert_float_eq(magnitude, filter.attenuation_at(freq));
         }
     }
 
-    fn weight_uncached(&self, i: i64, t: i64) -> f64 {
-        math::weights(
-            i,
-            self.radius as i64,
-            self.degree as i64,
-            t,
-            self.derivative as i64,
-        )
+    #[test]
+    fn derivative_in_place_matches_allocating_derivative() {
+        let filter = super::Filter::new(3, 3, 1);
+        let dx = 0.25;
+        let data: Vec<f64> = (0..30)
+            .map(|x| (x as f64 * 0.4).sin() * 5.0 + x as f64)
+            .collect();
+        let expected: Vec<f64> = filter.smooth(&data).iter().map(|d| d / dx).collect();
+        let mut in_place = data.clone();
+        filter.derivative_in_place(&mut in_place, dx);
+        for (actual, expected) in in_place.iter().zip(expected) {
+            assert_float_eq(*actual, expected);
+        }
     }
 
-    fn weight(&self, i: i64, t: i64) -> f64 {
-        self.weight_uncached(i, t)
+    #[test]
+    fn derivative_in_place_uses_interp_edges_in_mirror_mode() {
+        let filter = FilterBuilder::new()
+            .radius(3)
+            .degree(3)
+            .derivative(1)
+            .edge_mode(EdgeMode::Mirror)
+            .build()
+            .unwrap();
+        for len in [5, 30] {
+            let data: Vec<f64> = (0..len)
+                .map(|x| (x as f64 * 0.4).sin() * 5.0 + x as f64)
+                .collect();
+            let expected: Vec<f64> = filter
+                .smooth_with(&data, EdgeMode::Interp)
+                .iter()
+                .map(|d| d / 0.25)
+                .collect();
+            let mut in_place = data.clone();
+            filter.derivative_in_place(&mut in_place, 0.25);
+            assert_all_float_eq(&in_place, &expected);
+        }
     }
 
-    /// Make sure you have a window of size 2 * RADIUS + 1
-    fn smooth_point(&self, t: i64, window: &[f64]) -> f64 {
-        assert!(window.len() == 2 * self.radius + 1);
-        let radius = self.radius as isize;
+    #[test]
+    fn derivative_in_place_window_exactly_fits() {
+        let filter = super::Filter::new(2, 2, 1);
+        // y = x^2 sampled at x = 0, 0.5, ..., 2.0, so dy/dx = 2x.
+        let mut data: Vec<f64> = (0..5).map(|x| (x as f64 * 0.5).powi(2)).collect();
+        filter.derivative_in_place(&mut data, 0.5);
+        for (x, slope) in data.iter().enumerate() {
+            assert_float_eq(*slope, 2.0 * x as f64 * 0.5);
+        }
+    }
 
-        let mut sum = 0.0;
-        for i in -radius..=radius {
-            sum += self.weight(i as i64, t) * window[(i + radius) as usize];
+    #[test]
+    fn smooth_rate_limited_ramps_through_jump() {
+        let filter = super::Filter::new(1, 1, 0);
+        let mut data = vec![0.0; 10];
+        data.extend(vec![10.0; 10]);
+        let limited = filter.smooth_rate_limited(&data, 1.0);
+        for pair in limited.windows(2) {
+            assert!((pair[1] - pair[0]).abs() <= 1.0 + 1e-12);
         }
-        sum
+        assert_float_eq(limited[10] - limited[9], 1.0);
+        assert_float_eq(limited[19], 10.0);
     }
 
-    fn smooth_edge(&self, start_t: isize, end_t: isize, window: &[f64]) -> Vec<f64> {
-        let mut smoothed = Vec::new();
-        for t in start_t..=end_t {
-            smoothed.push(self.smooth_point(t as i64, window));
+    #[test]
+    fn smooth_rate_limited_leaves_gentle_signal_unchanged() {
+        let filter = super::Filter::new(2, 2, 0);
+        let data: Vec<f64> = (0..20).map(|x| x as f64 * 0.1).collect();
+        assert_eq!(filter.smooth_rate_limited(&data, 1.0), filter.smooth(&data));
+    }
+
+    #[test]
+    fn smooth_rate_limited_skips_nan() {
+        let filter = super::Filter::new(1, 1, 0);
+        let mut data = vec![0.0; 10];
+        data.extend(vec![10.0; 10]);
+        data[4] = f64::NAN;
+        let limited = filter.smooth_rate_limited(&data, 1.0);
+        assert!(limited[3..6].iter().all(|x| x.is_nan()));
+        assert_float_eq(limited[6], 0.0);
+        assert_float_eq(limited[10] - limited[9], 1.0);
+        assert_float_eq(limited[19], 10.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "max_delta must be non-negative")]
+    fn smooth_rate_limited_rejects_negative_limit() {
+        super::Filter::new(1, 1, 0).smooth_rate_limited(&[1.0, 2.0, 3.0], -1.0);
+    }
+
+    #[test]
+    fn smoothing_matrix_banded_apply_matches_smooth() {
+        let filter = super::Filter::new(3, 2, 1);
+        for len in [2, 5, 7, 30] {
+            let data: Vec<f64> = (0..len)
+                .map(|x| (x as f64 * 0.7).cos() * x as f64)
+                .collect();
+            let matrix = filter.smoothing_matrix_banded(len);
+            for (actual, expected) in matrix.apply(&data).iter().zip(filter.smooth(&data)) {
+                assert_float_eq(*actual, expected);
+            }
         }
-        smoothed
     }
 
-    pub fn smooth(&self, data: &[f64]) -> Vec<f64> {
-        if data.len() <= 2 {
-            return data.to_vec();
-        }
-        if data.len() < 2 * self.radius + 1 {
-            let radius = (data.len() - 1) / 2;
-            return Filter::new(radius, self.degree, self.derivative).smooth(data);
-        }
-        let mut smoothed = Vec::new();
-        smoothed.extend(self.smooth_edge(
-            -(self.radius as isize),
-            -1,
-            data[0..2 * self.radius + 1].as_ref(),
-        ));
-        for i in self.radius..data.len() - self.radius {
-            let window = &data[i - self.radius..=i + self.radius];
-            smoothed.push(self.smooth_point(0, window));
-        }
-        smoothed.extend(self.smooth_edge(
-            1,
-            self.radius as isize,
-            data[data.len() - 2 * self.radius - 1..].as_ref(),
-        ));
-        smoothed
+    #[test]
+    fn smoothing_matrix_banded_uses_interp_edges() {
+        let filter = FilterBuilder::new()
+            .radius(2)
+            .edge_mode(EdgeMode::Mirror)
+            .build()
+            .unwrap();
+        for len in [2, 3, 12] {
+            let data: Vec<f64> = (0..len).map(|x| (x as f64 * 0.9).sin()).collect();
+            assert_all_float_eq(
+                &filter.smoothing_matrix_banded(len).apply(&data),
+                &filter.smooth_with(&data, EdgeMode::Interp),
+            );
+        }
+        let data: Vec<f64> = (0..12).map(|x| (x as f64 * 0.9).sin()).collect();
+        let variance = filter.smooth_with_variance(&data, 1.0);
+        let values: Vec<f64> = variance.iter().map(|&(value, _)| value).collect();
+        assert_all_float_eq(&values, &super::Filter::new(2, 2, 0).smooth(&data));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use approx::assert_relative_eq;
+    #[test]
+    fn smoothing_matrix_banded_storage_is_independent_of_n() {
+        let filter = super::Filter::new(2, 2, 0);
+        let matrix = filter.smoothing_matrix_banded(100_000);
+        assert_eq!(matrix.n(), 100_000);
+        assert_eq!(matrix.stored_len(), 5 * 5);
+        assert_float_eq(matrix.get(500, 498), -3.0 / 35.0);
+        assert_float_eq(matrix.get(500, 500), 17.0 / 35.0);
+        assert_float_eq(matrix.get(500, 503), 0.0);
+        assert_float_eq(matrix.get(0, 0), 31.0 / 35.0);
+        assert_float_eq(matrix.get(99_999, 99_999), 31.0 / 35.0);
+        assert_float_eq(matrix.get(0, 99_999), 0.0);
+    }
 
-    fn assert_float_eq(a: f64, b: f64) {
-        assert_relative_eq!(a, b, epsilon = 1e-10);
+    #[test]
+    fn smooth_precomputed_kernel_matches_smooth_point() {
+        let filter = super::Filter::new(4, 3, 0);
+        let data: Vec<f64> = (0..10_000)
+            .map(|x| (x as f64 * 0.01).sin() + (x % 7) as f64 * 0.1)
+            .collect();
+        let smoothed = filter.smooth(&data);
+        for i in 4..data.len() - 4 {
+            assert_eq!(smoothed[i], filter.smooth_point(0, &data[i - 4..=i + 4]));
+        }
     }
 
     #[test]
-    fn smooth_two_points_is_unchanged() {
-        let filter = super::Filter::new(1, 2, 0);
-        let smoothed = filter.smooth(&[1.0, 2.0]);
-        assert_eq!(smoothed, vec![1.0, 2.0]);
+    fn getters_and_clone() {
+        let filter = super::Filter::new(3, 2, 1);
+        assert_eq!(
+            (filter.radius(), filter.degree(), filter.derivative()),
+            (3, 2, 1)
+        );
+        let asymmetric = super::Filter::new_asymmetric(4, 1, 2, 0);
+        assert_eq!(asymmetric.radius(), 4);
+        assert_eq!(
+            (asymmetric.radius_left(), asymmetric.radius_right()),
+            (4, 1)
+        );
+        let copy = filter;
+        assert_eq!(copy, filter.clone());
+        assert_ne!(copy, asymmetric);
+        assert_ne!(
+            filter,
+            FilterBuilder::new()
+                .radius(3)
+                .derivative(1)
+                .edge_mode(EdgeMode::Constant(0.0))
+                .build()
+                .unwrap()
+        );
+        assert!(format!("{:?}", filter).contains("degree: 2"));
     }
 
+    #[cfg(feature = "std")]
     #[test]
-    fn smooth_5pt_quadratic_on_7pts_linear() {
+    fn filters_are_hash_keys() {
+        let mut filters = std::collections::HashSet::new();
+        filters.insert(super::Filter::new(2, 2, 0));
+        filters.insert(super::Filter::savgol_5_2());
+        filters.insert(super::Filter::new(3, 2, 0));
+        filters.insert(FilterBuilder::new().radius(2).delta(0.5).build().unwrap());
+        assert_eq!(filters.len(), 3);
+        assert!(filters.contains(&super::Filter::default()));
+    }
+
+    #[test]
+    fn smooth_f32_ramp_is_exact() {
         let filter = super::Filter::new(2, 2, 0);
-        let data = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0];
-        let smoothed = filter.smooth(data.as_slice());
-        assert_eq!(smoothed.len(), 7);
-        assert_float_eq(smoothed[0], 1.0);
-        assert_float_eq(smoothed[1], 2.0);
-        assert_float_eq(smoothed[2], 3.0);
-        assert_float_eq(smoothed[3], 4.0);
-        assert_float_eq(smoothed[4], 5.0);
-        assert_float_eq(smoothed[5], 6.0);
-        assert_float_eq(smoothed[6], 7.0);
+        let data: Vec<f32> = (0..9).map(|x| x as f32).collect();
+        let smoothed: Vec<f32> = filter.smooth(&data);
+        assert_eq!(smoothed.len(), 9);
+        for (actual, expected) in smoothed.iter().zip(&data) {
+            assert_relative_eq!(*actual, *expected, epsilon = 1e-5);
+        }
     }
 
     #[test]
-    fn smooth_5pt_quadratic_on_7pts_nonlinear() {
+    fn smooth_f32_matches_f64() {
+        let filter = super::Filter::new(3, 3, 1);
+        let data: Vec<f64> = (0..20).map(|x| (x as f64 * 0.5).sin()).collect();
+        let data_f32: Vec<f32> = data.iter().map(|&x| x as f32).collect();
+        for (single, double) in filter.smooth(&data_f32).iter().zip(filter.smooth(&data)) {
+            assert_relative_eq!(*single as f64, double, epsilon = 1e-5);
+        }
+    }
+
+    fn assert_all_float_eq(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, b) in actual.iter().zip(expected) {
+            assert_float_eq(*a, *b);
+        }
+    }
+
+    #[test]
+    fn smooth_with_interp_matches_smooth() {
         let filter = super::Filter::new(2, 2, 0);
         let data = vec![1.0, -2.0, 3.0, -4.0, 5.0, -6.0, 7.0];
-        let smoothed = filter.smooth(data.as_slice());
+        assert_eq!(
+            filter.smooth_with(&data, EdgeMode::Interp),
+            filter.smooth(&data)
+        );
+    }
+
+    #[test]
+    fn smooth_with_modes_on_ramp() {
+        let filter = super::Filter::new(2, 2, 0);
+        let data = [0.0, 1.0, 2.0, 3.0, 4.0, 5.0];
+        let smooth = |mode| filter.smooth_with(&data, mode);
+        assert_all_float_eq(&smooth(EdgeMode::Interp), &data);
+        assert_all_float_eq(
+            &smooth(EdgeMode::Mirror),
+            &[
+                12.0 / 35.0,
+                29.0 / 35.0,
+                2.0,
+                3.0,
+                146.0 / 35.0,
+                163.0 / 35.0,
+            ],
+        );
+        assert_all_float_eq(
+            &smooth(EdgeMode::Nearest),
+            &[
+                6.0 / 35.0,
+                32.0 / 35.0,
+                2.0,
+                3.0,
+                143.0 / 35.0,
+                169.0 / 35.0,
+            ],
+        );
+        assert_all_float_eq(
+            &smooth(EdgeMode::Constant(0.0)),
+            &[
+                6.0 / 35.0,
+                32.0 / 35.0,
+                2.0,
+                3.0,
+                158.0 / 35.0,
+                124.0 / 35.0,
+            ],
+        );
+        assert_all_float_eq(
+            &smooth(EdgeMode::Wrap),
+            &[
+                54.0 / 35.0,
+                17.0 / 35.0,
+                2.0,
+                3.0,
+                158.0 / 35.0,
+                121.0 / 35.0,
+            ],
+        );
+    }
+
+    #[test]
+    fn smooth_with_modes_on_impulse() {
+        let filter = super::Filter::new(2, 2, 0);
+        let data = [1.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+        let smooth = |mode| filter.smooth_with(&data, mode);
+        let k = |x: f64| x / 35.0;
+        assert_all_float_eq(
+            &smooth(EdgeMode::Interp),
+            &[k(31.0), k(9.0), k(-3.0), 0.0, 0.0, 0.0],
+        );
+        assert_all_float_eq(
+            &smooth(EdgeMode::Mirror),
+            &[k(17.0), k(12.0), k(-3.0), 0.0, 0.0, 0.0],
+        );
+        assert_all_float_eq(
+            &smooth(EdgeMode::Nearest),
+            &[k(26.0), k(9.0), k(-3.0), 0.0, 0.0, 0.0],
+        );
+        assert_all_float_eq(
+            &smooth(EdgeMode::Constant(0.0)),
+            &[k(17.0), k(12.0), k(-3.0), 0.0, 0.0, 0.0],
+        );
+        assert_all_float_eq(
+            &smooth(EdgeMode::Wrap),
+            &[k(17.0), k(12.0), k(-3.0), 0.0, k(-3.0), k(12.0)],
+        );
+    }
+
+    #[test]
+    fn smooth_with_nearest_matches_scipy() {
+        // scipy.signal.savgol_filter(x, 5, 2, mode="nearest") and the same
+        // with deriv=1, which pad with the end values and then convolve.
+        let data = [2.0, -1.0, 4.0, 0.0, 3.0, 7.0];
+        let smoothed = super::Filter::new(2, 2, 0).smooth_with(&data, EdgeMode::Nearest);
+        assert_all_float_eq(
+            &smoothed,
+            &[
+                4.0 / 5.0,
+                7.0 / 5.0,
+                41.0 / 35.0,
+                66.0 / 35.0,
+                102.0 / 35.0,
+                218.0 / 35.0,
+            ],
+        );
+        let derivative = super::Filter::new(2, 2, 1).smooth_with(&data, EdgeMode::Nearest);
+        assert_all_float_eq(&derivative, &[0.1, -0.2, 0.3, 1.5, 1.3, 1.8]);
+
+        // Unlike the one-sided fits of Interp, which differ at the edges.
+        let interp = super::Filter::new(2, 2, 0).smooth(&data);
+        assert!((interp[0] - smoothed[0]).abs() > 0.1);
+        assert_float_eq(interp[2], smoothed[2]);
+    }
+
+    #[test]
+    fn coefficients_5pt_quadratic() {
+        let coefficients = super::Filter::new(2, 2, 0).coefficients();
+        assert_all_float_eq(
+            &coefficients,
+            &[
+                -3.0 / 35.0,
+                12.0 / 35.0,
+                17.0 / 35.0,
+                12.0 / 35.0,
+                -3.0 / 35.0,
+            ],
+        );
+        assert_float_eq(coefficients.iter().sum(), 1.0);
+    }
+
+    #[test]
+    fn smooth_scaled_first_derivative_uses_delta() {
+        let filter = super::Filter::new(2, 2, 1);
+        // y = 2x sampled at dt = 0.5.
+        let data: Vec<f64> = (0..10).map(|k| 2.0 * (k as f64 * 0.5)).collect();
+        for slope in filter.smooth(&data) {
+            assert_float_eq(slope, 1.0);
+        }
+        for slope in filter.smooth_scaled(&data, 0.5) {
+            assert_float_eq(slope, 2.0);
+        }
+    }
+
+    #[test]
+    fn smooth_scaled_without_derivative_is_smooth() {
+        let filter = super::Filter::new(2, 2, 0);
+        let data = vec![1.0, -2.0, 3.0, -4.0, 5.0, -6.0, 7.0];
+        assert_eq!(filter.smooth_scaled(&data, 0.1), filter.smooth(&data));
+    }
+
+    #[test]
+    fn smooth_into_matches_smooth() {
+        let filter = super::Filter::new(3, 2, 0);
+        let mut out = vec![0.0; 25];
+        for shift in 0..3 {
+            let data: Vec<f64> = (0..25).map(|x| ((x + shift) as f64).sqrt()).collect();
+            filter.smooth_into(&data, &mut out);
+            assert_eq!(out, filter.smooth(&data));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn smooth_into_rejects_mismatched_output() {
+        let filter = super::Filter::new(1, 1, 0);
+        filter.smooth_into(&[1.0, 2.0, 3.0], &mut [0.0; 2]);
+    }
+
+    #[test]
+    fn smooth_causal_reproduces_ramp() {
+        let filter = super::Filter::new(3, 2, 0);
+        let data: Vec<f64> = (0..20).map(|x| 3.0 * x as f64 - 1.0).collect();
+        assert_all_float_eq(&filter.smooth_causal(&data), &data);
+    }
+
+    #[test]
+    fn smooth_causal_slope_of_ramp() {
+        let filter = super::Filter::new(2, 1, 1);
+        let data: Vec<f64> = (0..10).map(|x| 3.0 * x as f64).collect();
+        let slopes = filter.smooth_causal(&data);
+        assert_float_eq(slopes[0], 0.0);
+        for slope in &slopes[1..] {
+            assert_float_eq(*slope, 3.0);
+        }
+    }
+
+    #[test]
+    fn integrate_constant_is_ramp() {
+        let filter = super::Filter::new(3, 2, 0);
+        let data = vec![2.0; 20];
+        let expected: Vec<f64> = (0..20).map(|k| 2.0 * 0.5 * k as f64).collect();
+        assert_all_float_eq(&filter.integrate(&data, 0.5), &expected);
+    }
+
+    #[test]
+    fn integrate_ramp_is_parabola() {
+        let data: Vec<f64> = (0..20).map(|k| k as f64 + 1.0).collect();
+        let expected: Vec<f64> = (0..20).map(|k| (k * k) as f64 / 2.0 + k as f64).collect();
+        let filter = super::Filter::new(3, 2, 0);
+        assert_all_float_eq(&filter.integrate(&data, 1.0), &expected);
+        let even = super::Filter::new_window(6, 2, 0);
+        assert_all_float_eq(&even.integrate(&data, 1.0), &expected);
+        assert!(filter.integrate(&[], 1.0).is_empty());
+    }
+
+    #[test]
+    fn smooth_filtfilt_has_no_phase_shift() {
+        let filter = super::Filter::new(4, 2, 0);
+        let data: Vec<f64> = (0..200)
+            .map(|k| (2.0 * core::f64::consts::PI * k as f64 / 40.0).sin())
+            .collect();
+        let causal = filter.smooth_causal(&data);
+        let filtfilt = filter.smooth_filtfilt(&data);
+        // Away from the edges the zero crossings of the sinusoid stay put,
+        // while the causal filter alone shifts them.
+        for k in (40..=160).step_by(20) {
+            assert!(filtfilt[k].abs() < 1e-10, "{}", filtfilt[k]);
+            assert!(causal[k].abs() > 1e-3, "{}", causal[k]);
+        }
+        for k in (50..=150).step_by(20) {
+            assert_eq!(filtfilt[k].signum(), data[k].signum());
+        }
+    }
+
+    #[test]
+    fn smooth_causal_ignores_future_samples() {
+        let filter = super::Filter::new(2, 2, 0);
+        let data: Vec<f64> = (0..15).map(|x| (x as f64).sin()).collect();
+        let mut changed = data.clone();
+        changed[10] = 100.0;
+        let before = filter.smooth_causal(&data);
+        let after = filter.smooth_causal(&changed);
+        assert_eq!(before[..10], after[..10]);
+        assert_ne!(before[10], after[10]);
+    }
+
+    #[test]
+    fn new_asymmetric_symmetric_matches_new() {
+        let symmetric = super::Filter::new_asymmetric(2, 2, 2, 0);
+        let data = vec![1.0, -2.0, 3.0, -4.0, 5.0, -6.0, 7.0];
+        assert_eq!(
+            symmetric.smooth(&data),
+            super::Filter::new(2, 2, 0).smooth(&data)
+        );
+        assert_all_float_eq(
+            &symmetric.smooth(&data),
+            &[
+                1.1142857142857143,
+                -0.8571428571428571,
+                -1.1142857142857143,
+                1.4857142857142858,
+                -1.8571428571428572,
+                0.17142857142857143,
+                5.057142857142857,
+            ],
+        );
+    }
+
+    #[test]
+    fn new_asymmetric_3_left_1_right_reproduces_ramp() {
+        let filter = super::Filter::new_asymmetric(3, 1, 2, 0);
+        let data: Vec<f64> = (0..12).map(|x| 2.0 * x as f64 + 1.0).collect();
+        assert_all_float_eq(&filter.smooth(&data), &data);
+        for slope in super::Filter::new_asymmetric(3, 1, 2, 1).smooth(&data) {
+            assert_float_eq(slope, 2.0);
+        }
+    }
+
+    #[test]
+    fn new_asymmetric_kernel_matches_one_sided_weights() {
+        // With 3 left and 1 right, t = 0 is one step right of the center of
+        // a symmetric 5-point window.
+        let coefficients = super::Filter::new_asymmetric(3, 1, 2, 0).coefficients();
+        let expected: Vec<f64> = (-2..=2).map(|i| math::weights(i, 2, 2, 1, 0)).collect();
+        assert_all_float_eq(&coefficients, &expected);
+    }
+
+    #[test]
+    fn new_asymmetric_even_window_reproduces_quadratic() {
+        let filter = super::Filter::new_asymmetric(2, 1, 2, 0);
+        let data: Vec<f64> = (0..10).map(|x| (x as f64 - 4.0).powi(2)).collect();
+        assert_all_float_eq(&filter.smooth(&data), &data);
+    }
+
+    #[test]
+    fn new_window_odd_matches_new() {
+        let data = vec![1.0, -2.0, 3.0, -4.0, 5.0, -6.0, 7.0];
+        assert_eq!(
+            super::Filter::new_window(5, 2, 0).smooth(&data),
+            super::Filter::new(2, 2, 0).smooth(&data)
+        );
+    }
+
+    #[test]
+    fn new_window_4pt_quadratic_kernel() {
+        // Least squares quadratic through x = -1.5, -0.5, 0.5, 1.5, evaluated
+        // at x = 0: the weights are (10.25 - 5 x^2) / 16.
+        let filter = super::Filter::new_window(4, 2, 0);
+        assert_all_float_eq(
+            &filter.coefficients(),
+            &[-1.0 / 16.0, 9.0 / 16.0, 9.0 / 16.0, -1.0 / 16.0],
+        );
+        let smoothed = filter.smooth(&[1.0, -2.0, 3.0, -4.0, 5.0, -6.0, 7.0]);
         assert_eq!(smoothed.len(), 7);
-        assert_float_eq(smoothed[0], 1.1142857142857143);
-        assert_float_eq(smoothed[1], -0.8571428571428571);
-        assert_float_eq(smoothed[2], -1.1142857142857143);
-        assert_float_eq(smoothed[3], 1.4857142857142858);
-        assert_float_eq(smoothed[4], -1.8571428571428572);
-        assert_float_eq(smoothed[5], 0.17142857142857143);
-        assert_float_eq(smoothed[6], 5.057142857142857);
+        assert_float_eq(smoothed[2], 0.75);
+        assert_float_eq(smoothed[3], -0.75);
+        assert_float_eq(smoothed[5], -0.75);
     }
 
     #[test]
-    fn smooth_5pt_quadratic_on_5pts_nonlinear_radius_too_large() {
-        let filter = super::Filter::new(20, 2, 0);
-        let data = vec![1.0, -2.0, 3.0, -4.0, 5.0];
-        let smoothed = filter.smooth(data.as_slice());
-        assert_eq!(smoothed.len(), 5);
-        assert_float_eq(smoothed[0], 1.1142857142857143);
-        assert_float_eq(smoothed[1], -0.8571428571428571);
-        assert_float_eq(smoothed[2], -1.1142857142857143);
-        assert_float_eq(smoothed[3], 0.34285714285714286);
-        assert_float_eq(smoothed[4], 3.5142857142857142);
+    fn new_window_even_evaluates_half_step_back() {
+        let data: Vec<f64> = (0..9).map(|x| (x * x) as f64).collect();
+        let expected: Vec<f64> = (0..9).map(|x| (x as f64 - 0.5).powi(2)).collect();
+        let filter = super::Filter::new_window(4, 2, 0);
+        assert_all_float_eq(&filter.smooth(&data), &expected);
+        assert_all_float_eq(&filter.smooth(&data[..3]), &expected[..3]);
+        let slopes: Vec<f64> = (0..9).map(|x| 2.0 * x as f64 - 1.0).collect();
+        let derivative = super::Filter::new_window(6, 2, 1);
+        assert_all_float_eq(&derivative.smooth(&data), &slopes);
     }
 
     #[test]
-    fn smooth_5pt_quadratic_on_6pts_nonlinear_radius_too_large() {
-        let filter = super::Filter::new(20, 2, 0);
-        let data = vec![1.0, -2.0, 3.0, -4.0, 5.0, -6.0];
-        let smoothed = filter.smooth(data.as_slice());
-        assert_eq!(smoothed.len(), 6);
-        assert_float_eq(smoothed[0], 1.1142857142857143);
-        assert_float_eq(smoothed[1], -0.8571428571428571);
-        assert_float_eq(smoothed[2], -1.1142857142857143);
-        assert_float_eq(smoothed[3], 1.4857142857142858);
-        assert_float_eq(smoothed[4], -0.2571428571428571);
-        assert_float_eq(smoothed[5], -4.285714285714286);
+    fn try_new_window_rejects_short_windows() {
+        assert_eq!(
+            super::Filter::try_new_window(1, 0, 0).err(),
+            Some(FilterError::ZeroRadius)
+        );
+        assert_eq!(
+            super::Filter::try_new_window(4, 4, 0).err(),
+            Some(FilterError::DegreeExceedsWindow {
+                degree: 4,
+                window_length: 4
+            })
+        );
+    }
+
+    #[test]
+    fn smooth_nan_without_nans_matches_smooth() {
+        let filter = super::Filter::new(2, 2, 0);
+        let data = [1.0, -2.0, 3.0, -4.0, 5.0, -6.0, 7.0];
+        assert_all_float_eq(&filter.smooth_nan(&data), &filter.smooth(&data));
+    }
+
+    #[test]
+    fn smooth_nan_isolated_gap_fills_quadratic() {
+        let mut data: Vec<f64> = (0..10).map(|x| (x as f64 - 3.0).powi(2)).collect();
+        let expected = data.clone();
+        data[4] = f64::NAN;
+        data[8] = f64::NAN;
+        let smoothed = super::Filter::new(2, 2, 0).smooth_nan(&data);
+        assert_all_float_eq(&smoothed, &expected);
+        let slopes = super::Filter::new(2, 2, 1).smooth_nan(&data);
+        for (x, slope) in slopes.iter().enumerate() {
+            assert_float_eq(*slope, 2.0 * (x as f64 - 3.0));
+        }
+    }
+
+    #[test]
+    fn smooth_nan_cluster_too_large_gives_nan() {
+        let mut data: Vec<f64> = (0..12).map(|x| 0.5 * x as f64).collect();
+        for x in &mut data[4..7] {
+            *x = f64::NAN;
+        }
+        let smoothed = super::Filter::new(2, 2, 0).smooth_nan(&data);
+        // The windows around 4..=6 keep only two samples each.
+        assert!(smoothed[4..7].iter().all(|x| x.is_nan()));
+        for k in [0, 1, 2, 3, 7, 8, 9, 10, 11] {
+            assert_float_eq(smoothed[k], 0.5 * k as f64);
+        }
+    }
+
+    #[test]
+    fn smooth_weighted_uniform_matches_smooth() {
+        let filter = super::Filter::new(3, 3, 1);
+        let data: Vec<f64> = (0..15).map(|x| (x as f64 * 0.4).sin()).collect();
+        let weights = vec![2.5; data.len()];
+        assert_all_float_eq(
+            &filter.smooth_weighted(&data, &weights),
+            &filter.smooth(&data),
+        );
+    }
+
+    #[test]
+    fn smooth_weighted_zero_weight_matches_nan_skip() {
+        let filter = super::Filter::new(2, 2, 0);
+        let data = [1.0, -2.0, 3.0, -4.0, 5.0, -6.0, 7.0, -8.0];
+        let mut with_nan = data;
+        with_nan[3] = f64::NAN;
+        let mut weights = [1.0; 8];
+        weights[3] = 0.0;
+        assert_all_float_eq(
+            &filter.smooth_weighted(&data, &weights),
+            &filter.smooth_nan(&with_nan),
+        );
+    }
+
+    #[test]
+    fn smooth_weighted_pulls_toward_heavy_samples() {
+        let filter = super::Filter::new(2, 0, 0);
+        let data = [0.0, 0.0, 10.0, 0.0, 0.0];
+        let light = filter.smooth_weighted(&data, &[1.0; 5]);
+        let heavy = filter.smooth_weighted(&data, &[1.0, 1.0, 4.0, 1.0, 1.0]);
+        assert_float_eq(light[2], 2.0);
+        assert_float_eq(heavy[2], 5.0);
+    }
+
+    #[test]
+    fn smooth_xy_uniform_matches_smooth() {
+        let filter = super::Filter::new(2, 2, 1);
+        let x: Vec<f64> = (0..9).map(|i| i as f64).collect();
+        let y = [1.0, -2.0, 3.0, -4.0, 5.0, -6.0, 7.0, -8.0, 9.0];
+        assert_all_float_eq(&filter.smooth_xy(&x, &y), &filter.smooth(&y));
+    }
+
+    #[test]
+    fn smooth_xy_recovers_quadratic_at_irregular_positions() {
+        let x = [0.0, 0.13, 0.9, 1.05, 2.7, 3.1, 3.15, 4.6, 5.9, 7.2, 7.25];
+        let f = |x: f64| 3.0 * x * x - 2.0 * x + 0.5;
+        let y: Vec<f64> = x.iter().map(|&x| f(x)).collect();
+        assert_all_float_eq(&super::Filter::new(2, 2, 0).smooth_xy(&x, &y), &y);
+        let slopes = super::Filter::new(3, 2, 1).smooth_xy(&x, &y);
+        let expected: Vec<f64> = x.iter().map(|&x| 6.0 * x - 2.0).collect();
+        assert_all_float_eq(&slopes, &expected);
+    }
+
+    #[test]
+    fn fit_recovers_cubic_coefficients() {
+        // f(x) = 0.5 - x + 0.25 x^2 + 0.1 x^3, expanded around each k.
+        let f = |x: f64| 0.5 - x + 0.25 * x * x + 0.1 * x * x * x;
+        let data: Vec<f64> = (0..12).map(|x| f(x as f64)).collect();
+        let fits = super::Filter::new(3, 3, 0).fit(&data);
+        assert_eq!(fits.len(), data.len());
+        for (k, coefficients) in fits.iter().enumerate() {
+            let x = k as f64;
+            let expected = [f(x), -1.0 + 0.5 * x + 0.3 * x * x, 0.25 + 0.3 * x, 0.1];
+            assert_eq!(coefficients.len(), 4);
+            for (a, b) in coefficients.iter().zip(expected) {
+                assert_relative_eq!(*a, b, epsilon = 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn interpolate_quadratic_at_half_samples() {
+        let f = |x: f64| 2.0 * x * x - 3.0 * x + 1.0;
+        let data: Vec<f64> = (0..10).map(|x| f(x as f64)).collect();
+        let positions: Vec<f64> = (0..20).map(|i| i as f64 * 0.5 - 0.5).collect();
+        let expected: Vec<f64> = positions.iter().map(|&x| f(x)).collect();
+        let filter = super::Filter::new(2, 2, 0);
+        assert_all_float_eq(&filter.interpolate(&data, &positions), &expected);
+        let slopes: Vec<f64> = positions.iter().map(|&x| 4.0 * x - 3.0).collect();
+        let derivative = super::Filter::new(2, 2, 1);
+        assert_all_float_eq(&derivative.interpolate(&data, &positions), &slopes);
+    }
+
+    #[test]
+    fn interpolate_at_samples_matches_smooth() {
+        let filter = super::Filter::new(2, 2, 0);
+        let data = [1.0, -2.0, 3.0, -4.0, 5.0, -6.0, 7.0];
+        let positions: Vec<f64> = (0..7).map(|x| x as f64).collect();
+        assert_all_float_eq(
+            &filter.interpolate(&data, &positions),
+            &filter.smooth(&data),
+        );
+    }
+
+    #[test]
+    fn smooth_derivatives_matches_separate_filters() {
+        let data: Vec<f64> = (0..20).map(|x| (x as f64 * 0.45).sin() * 3.0).collect();
+        let filter = super::Filter::new_asymmetric(3, 2, 3, 0);
+        let outputs = filter.smooth_derivatives(&data, &[0, 1, 2]);
+        assert_eq!(outputs.len(), 3);
+        for (order, output) in outputs.iter().enumerate() {
+            let separate = super::Filter::new_asymmetric(3, 2, 3, order as u64).smooth(&data);
+            assert_all_float_eq(output, &separate);
+        }
+        let short = super::Filter::new(3, 2, 0).smooth_derivatives(&data[..5], &[2, 0]);
+        assert_all_float_eq(&short[0], &super::Filter::new(3, 2, 2).smooth(&data[..5]));
+        assert_all_float_eq(&short[1], &super::Filter::new(3, 2, 0).smooth(&data[..5]));
+    }
+
+    #[test]
+    fn smooth_value_and_derivative_on_parabola() {
+        // y = x^2 - 3x sampled at dx = 0.5, so dy/dx = 2x - 3.
+        let xs: Vec<f64> = (0..12).map(|k| k as f64 * 0.5).collect();
+        let data: Vec<f64> = xs.iter().map(|x| x * x - 3.0 * x).collect();
+        let pairs = super::Filter::new(3, 2, 1).smooth_value_and_derivative(&data, 0.5);
+        assert_eq!(pairs.len(), data.len());
+        for k in [0, 2, 5, 9, 11] {
+            assert_float_eq(pairs[k][0], data[k]);
+            assert_float_eq(pairs[k][1], 2.0 * xs[k] - 3.0);
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn smooth_parallel_matches_smooth() {
+        let data: Vec<f64> = (0..1_000_000)
+            .map(|x| (x as f64 * 0.001).sin() + (x % 7) as f64 * 0.1)
+            .collect();
+        let filter = super::Filter::new_asymmetric(4, 3, 3, 1);
+        assert_eq!(filter.smooth_parallel(&data), filter.smooth(&data));
+        for len in [0, 1, 5, 8] {
+            assert_eq!(
+                filter.smooth_parallel(&data[..len]),
+                filter.smooth(&data[..len])
+            );
+        }
+    }
+
+    #[test]
+    fn smooth_batch_matches_per_series() {
+        let filter = super::Filter::new(3, 2, 1);
+        let series: Vec<Vec<f64>> = (0..20)
+            .map(|channel| {
+                (0..channel * 3)
+                    .map(|x| (x as f64 * 0.2 + channel as f64).cos())
+                    .collect()
+            })
+            .collect();
+        let expected: Vec<Vec<f64>> = series.iter().map(|data| filter.smooth(data)).collect();
+        assert_eq!(filter.smooth_batch(&series), expected);
+        #[cfg(feature = "rayon")]
+        assert_eq!(filter.smooth_batch_parallel(&series), expected);
+    }
+
+    #[test]
+    fn smooth_point_compensates_wide_magnitudes() {
+        // Even data over a symmetric window has zero first derivative at the
+        // center, but the terms span 16 orders of magnitude.
+        let radius = 400;
+        let filter = super::Filter::new(radius, 3, 1);
+        let window: Vec<f64> = (-(radius as i32)..=radius as i32)
+            .map(|i| {
+                let sign = if i.abs() % 3 == 0 { -1.0 } else { 1.0 };
+                sign * 10f64.powi(i.abs() % 17)
+            })
+            .collect();
+        let naive: f64 = filter
+            .kernel(0)
+            .iter()
+            .zip(&window)
+            .map(|(w, x)| w * x)
+            .fold(0.0, |sum, term| sum + term);
+        let compensated = filter.smooth_point(0, &window);
+        assert!(compensated.abs() < 1e-12);
+        assert!(compensated.abs() < naive.abs());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_json_round_trip() {
+        let filter = super::Filter::new(3, 2, 1);
+        let json = serde_json::to_string(&filter).unwrap();
+        let restored: super::Filter = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, filter);
+
+        let mode = EdgeMode::Constant(2.5);
+        let json = serde_json::to_string(&mode).unwrap();
+        assert_eq!(serde_json::from_str::<EdgeMode>(&json).unwrap(), mode);
+        let error = FilterError::ZeroRadius;
+        let json = serde_json::to_string(&error).unwrap();
+        assert_eq!(serde_json::from_str::<FilterError>(&json).unwrap(), error);
     }
 
     #[test]
@@ -189,4 +3783,11 @@ mod tests {
         let smoothed = filter.smooth_point(-2, vec![1.0, -2.0, 3.0, -4.0, 5.0].as_slice());
         assert_float_eq(smoothed, 1.1142857142857143);
     }
+
+    #[test]
+    fn filters_are_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<super::Filter>();
+        assert_send_sync::<crate::PrecomputedFilter>();
+    }
 }
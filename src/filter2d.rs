@@ -0,0 +1,328 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use core::cmp::Ordering;
+use core::ops::Range;
+
+use crate::error::FilterError;
+use crate::filter::{MAX_DEGREE, MAX_SPAN};
+use crate::math::WeightTable;
+use crate::precomputed::dot;
+
+/// A two-dimensional Savitzky–Golay filter for images and other gridded data.
+///
+/// Each pixel is replaced by a least-squares fit of the polynomial
+/// `sum(a_pq * x^p * y^q)` over all `p + q <= degree` to its
+/// `(2 * radius + 1)^2` neighborhood, or by the fit's partial derivative
+/// `deriv_x` times in `x` (along rows) and `deriv_y` times in `y` (down
+/// columns). As in one dimension, pixels near the border use the nearest full
+/// neighborhood with the fit evaluated off-center.
+///
+/// The fit is expanded in the products `P_p(x) * P_q(y)` of the 1-D Gram
+/// polynomials, which are orthogonal over the square neighborhood, so each
+/// weight is a sum of products of the 1-D weight terms and no normal
+/// equations are solved. Only those terms and the interior kernel are
+/// stored, `O(radius^2 * degree)` values; the kernels for pixels near the
+/// border are built from the terms when an image is smoothed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter2D {
+    radius: usize,
+    degree: u64,
+    deriv_x: u64,
+    deriv_y: u64,
+    /// `x_terms[tx + radius][i + radius][p]` is term `p` of the 1-D weight
+    /// of column offset `i` for the `deriv_x`'th derivative at `tx`.
+    x_terms: Vec<Vec<Vec<f64>>>,
+    /// `y_sums[ty + radius][j + radius][c]` is the sum of terms `0..=c` of
+    /// the 1-D weight of row offset `j` for the `deriv_y`'th derivative at
+    /// `ty`.
+    y_sums: Vec<Vec<Vec<f64>>>,
+    /// The kernel of pixels at least `radius` from every border, laid out
+    /// in the same row-major order as the neighborhood.
+    kernel: Vec<f64>,
+}
+
+impl Filter2D {
+    /// Creates a filter, panicking if the parameters are invalid. See
+    /// `try_new` for the conditions checked.
+    pub fn new(radius: usize, degree: u64, deriv_x: u64, deriv_y: u64) -> Self {
+        Filter2D::try_new(radius, degree, deriv_x, deriv_y).unwrap()
+    }
+
+    /// Creates a filter over a `(2 * radius + 1)^2` neighborhood.
+    ///
+    /// Returns an error unless `radius >= 1`, `degree <= 2 * radius`, and
+    /// `deriv_x + deriv_y <= degree`, `RadiusTooLarge` if the neighborhood
+    /// has too many points to count, or `DegreeTooLarge` if the degree is
+    /// above `i32::MAX`.
+    pub fn try_new(
+        radius: usize,
+        degree: u64,
+        deriv_x: u64,
+        deriv_y: u64,
+    ) -> Result<Self, FilterError> {
+        if radius == 0 {
+            return Err(FilterError::ZeroRadius);
+        }
+        // The neighborhood has (2 * radius + 1)^2 points.
+        let side = radius
+            .checked_mul(2)
+            .and_then(|span| span.checked_add(1))
+            .filter(|&side| side as u64 <= MAX_SPAN);
+        if side.and_then(|side| side.checked_mul(side)).is_none() {
+            return Err(FilterError::RadiusTooLarge);
+        }
+        if degree > MAX_DEGREE {
+            return Err(FilterError::DegreeTooLarge { degree });
+        }
+        if degree > 2 * radius as u64 {
+            return Err(FilterError::DegreeExceedsWindow {
+                degree,
+                window_length: 2 * radius + 1,
+            });
+        }
+        let derivative = deriv_x.saturating_add(deriv_y);
+        if derivative > degree {
+            return Err(FilterError::DerivativeExceedsDegree { derivative, degree });
+        }
+        Ok(Filter2D::new_unchecked(radius, degree, deriv_x, deriv_y))
+    }
+
+    /// Creates a filter without validating it. A derivative beyond `degree`
+    /// gives all-zero kernels.
+    fn new_unchecked(radius: usize, degree: u64, deriv_x: u64, deriv_y: u64) -> Self {
+        let r = radius as i64;
+        let table = WeightTable::new(2 * r, degree as i64);
+        let terms = |s: u64| -> Vec<Vec<Vec<f64>>> {
+            (-r..=r)
+                .map(|t| {
+                    (-r..=r)
+                        .map(|i| table.terms(2 * i, 2 * t, s as i64))
+                        .collect()
+                })
+                .collect()
+        };
+        let mut y_sums = terms(deriv_y);
+        for sums in y_sums.iter_mut().flatten() {
+            for c in 1..sums.len() {
+                sums[c] += sums[c - 1];
+            }
+        }
+        let mut filter = Filter2D {
+            radius,
+            degree,
+            deriv_x,
+            deriv_y,
+            x_terms: terms(deriv_x),
+            y_sums,
+            kernel: Vec::new(),
+        };
+        filter.kernel = filter.kernel_at(0, 0);
+        filter
+    }
+
+    /// The kernel for the fit evaluated at offset `(tx, ty)` from the
+    /// center of the neighborhood. Of the products of 1-D terms `p` in `x`
+    /// and `q` in `y`, only those with `p + q <= degree` belong to the fit.
+    fn kernel_at(&self, tx: i64, ty: i64) -> Vec<f64> {
+        let r = self.radius as i64;
+        let n = self.degree as usize;
+        let x_terms = &self.x_terms[(tx + r) as usize];
+        let y_sums = &self.y_sums[(ty + r) as usize];
+        y_sums
+            .iter()
+            .flat_map(|y| {
+                x_terms
+                    .iter()
+                    .map(move |x| (0..=n).map(|p| x[p] * y[n - p]).sum())
+            })
+            .collect()
+    }
+
+    /// Smooths a `width x height` image stored row by row.
+    ///
+    /// Images narrower or shorter than the neighborhood are smoothed with
+    /// the largest neighborhood that fits, with `degree` lowered to at most
+    /// twice its radius. The derivatives are kept, so any of total order
+    /// `deriv_x + deriv_y` above the lowered degree is zero, as for a
+    /// polynomial of that degree: an image one or two pixels wide or tall
+    /// has a constant fit and all-zero derivatives.
+    pub fn smooth(&self, image: &[f64], width: usize, height: usize) -> Vec<f64> {
+        assert_eq!(image.len(), width * height);
+        if image.is_empty() {
+            return Vec::new();
+        }
+        let side = 2 * self.radius + 1;
+        if width < side || height < side {
+            return self.shrunk(width.min(height)).smooth(image, width, height);
+        }
+        let r = self.radius as i64;
+        let mut window = vec![0.0; side * side];
+        let mut smoothed = vec![0.0; image.len()];
+        // Every pixel sharing an evaluation offset shares a kernel, so each
+        // border kernel is built once.
+        for ty in -r..=r {
+            for tx in -r..=r {
+                let border_kernel;
+                let kernel = if (tx, ty) == (0, 0) {
+                    &self.kernel
+                } else {
+                    border_kernel = self.kernel_at(tx, ty);
+                    &border_kernel
+                };
+                for y in positions(height, self.radius, ty) {
+                    let start_y = (y as i64 - ty - r) as usize;
+                    for x in positions(width, self.radius, tx) {
+                        let start_x = (x as i64 - tx - r) as usize;
+                        for (row, values) in window.chunks_mut(side).enumerate() {
+                            let offset = (start_y + row) * width + start_x;
+                            values.copy_from_slice(&image[offset..offset + side]);
+                        }
+                        smoothed[y * width + x] = dot(kernel, &window);
+                    }
+                }
+            }
+        }
+        smoothed
+    }
+
+    /// The filter `smooth` falls back to for images whose smaller side is
+    /// `len`.
+    fn shrunk(&self, len: usize) -> Filter2D {
+        let radius = (len - 1) / 2;
+        let degree = self.degree.min(2 * radius as u64);
+        Filter2D::new_unchecked(radius, degree, self.deriv_x, self.deriv_y)
+    }
+}
+
+/// The positions along a side of `len` pixels whose fit is evaluated at
+/// offset `t` from the center of their neighborhood: one pixel near each
+/// end, and every pixel at least `radius` from both ends for `t == 0`.
+fn positions(len: usize, radius: usize, t: i64) -> Range<usize> {
+    match t.cmp(&0) {
+        Ordering::Less => {
+            let position = (radius as i64 + t) as usize;
+            position..position + 1
+        }
+        Ordering::Equal => radius..len - radius,
+        Ordering::Greater => {
+            let position = len - 1 - radius + t as usize;
+            position..position + 1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use approx::assert_relative_eq;
+
+    use super::Filter2D;
+    use crate::FilterError;
+
+    fn assert_all_float_eq(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, b) in actual.iter().zip(expected) {
+            assert_relative_eq!(a, b, epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn smooth_constant_image_is_unchanged() {
+        let image = vec![3.5; 8 * 6];
+        let smoothed = Filter2D::new(2, 2, 0, 0).smooth(&image, 8, 6);
+        assert_all_float_eq(&smoothed, &image);
+    }
+
+    #[test]
+    fn x_gradient_of_linear_ramp() {
+        let (width, height) = (9, 7);
+        let image: Vec<f64> = (0..width * height)
+            .map(|k| 2.0 * (k % width) as f64 - 0.5 * (k / width) as f64 + 1.0)
+            .collect();
+        let gradient_x = Filter2D::new(2, 2, 1, 0).smooth(&image, width, height);
+        assert_all_float_eq(&gradient_x, &vec![2.0; width * height]);
+        let gradient_y = Filter2D::new(2, 2, 0, 1).smooth(&image, width, height);
+        assert_all_float_eq(&gradient_y, &vec![-0.5; width * height]);
+    }
+
+    #[test]
+    fn smooth_reproduces_quadratic_surface() {
+        let (width, height) = (7, 8);
+        let f = |x: f64, y: f64| x * x - 2.0 * x * y + 0.5 * y * y + y;
+        let image: Vec<f64> = (0..width * height)
+            .map(|k| f((k % width) as f64, (k / width) as f64))
+            .collect();
+        let smoothed = Filter2D::new(1, 2, 0, 0).smooth(&image, width, height);
+        assert_all_float_eq(&smoothed, &image);
+        let mixed = Filter2D::new(2, 2, 1, 1).smooth(&image, width, height);
+        assert_all_float_eq(&mixed, &vec![-2.0; width * height]);
+    }
+
+    #[test]
+    fn high_degree_surface_is_reproduced_at_every_offset() {
+        let (width, height) = (20, 18);
+        let f = |x: f64, y: f64| {
+            let (u, v) = (x / 10.0 - 1.0, y / 9.0 - 1.0);
+            u.powi(6) * v.powi(4) - 3.0 * u.powi(7) * v + v.powi(10) + u * v
+        };
+        let image: Vec<f64> = (0..width * height)
+            .map(|k| f((k % width) as f64, (k / width) as f64))
+            .collect();
+        let smoothed = Filter2D::new(6, 10, 0, 0).smooth(&image, width, height);
+        for (actual, expected) in smoothed.iter().zip(&image) {
+            assert_relative_eq!(actual, expected, epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn smooth_small_image_shrinks_neighborhood() {
+        let image: Vec<f64> = (0..12)
+            .map(|k| (k % 4) as f64 + 3.0 * (k / 4) as f64)
+            .collect();
+        let smoothed = Filter2D::new(3, 2, 0, 0).smooth(&image, 4, 3);
+        assert_all_float_eq(&smoothed, &image);
+    }
+
+    #[test]
+    fn small_image_derivatives() {
+        let f = |x: f64, y: f64| x * x + 3.0 * x * y - y;
+        let image: Vec<f64> = (0..9).map(|k| f((k % 3) as f64, (k / 3) as f64)).collect();
+        let mixed = Filter2D::new(2, 2, 1, 1).smooth(&image, 3, 3);
+        assert_all_float_eq(&mixed, &[3.0; 9]);
+        let gradient_y = Filter2D::new(2, 2, 0, 1).smooth(&image, 3, 3);
+        let expected: Vec<f64> = (0..9).map(|k| 3.0 * (k % 3) as f64 - 1.0).collect();
+        assert_all_float_eq(&gradient_y, &expected);
+        let row = [1.0, 4.0, 9.0, 16.0, 25.0];
+        assert_all_float_eq(&Filter2D::new(2, 2, 1, 0).smooth(&row, 5, 1), &[0.0; 5]);
+    }
+
+    #[test]
+    fn try_new_rejects_invalid_parameters() {
+        assert_eq!(Filter2D::try_new(0, 0, 0, 0), Err(FilterError::ZeroRadius));
+        assert_eq!(
+            Filter2D::try_new(usize::MAX / 2, 2, 0, 0),
+            Err(FilterError::RadiusTooLarge)
+        );
+        assert_eq!(
+            Filter2D::try_new(1, u64::MAX, 0, 0),
+            Err(FilterError::DegreeTooLarge { degree: u64::MAX })
+        );
+        assert_eq!(
+            Filter2D::try_new(1, 2, u64::MAX, 1),
+            Err(FilterError::DerivativeExceedsDegree {
+                derivative: u64::MAX,
+                degree: 2
+            })
+        );
+        assert_eq!(
+            Filter2D::try_new(1, 2, 2, 1),
+            Err(FilterError::DerivativeExceedsDegree {
+                derivative: 3,
+                degree: 2
+            })
+        );
+    }
+}
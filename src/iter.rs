@@ -0,0 +1,118 @@
+use alloc::collections::VecDeque;
+
+use crate::filter::Filter;
+use crate::online::OnlineFilter;
+
+/// Adds `savgol_smooth` to iterators of `f64`.
+pub trait SmoothExt: Iterator<Item = f64> + Sized {
+    /// Lazily smooths the items of this iterator with `filter`, yielding the
+    /// same values as `filter.smooth` on the collected items (with `Interp`
+    /// edges, as in `OnlineFilter`).
+    fn savgol_smooth(self, filter: Filter) -> SmoothIter<Self> {
+        SmoothIter {
+            source: Some(self),
+            online: OnlineFilter::new(&filter),
+            started: false,
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+impl<I: Iterator<Item = f64>> SmoothExt for I {}
+
+/// Iterator returned by `SmoothExt::savgol_smooth`.
+///
+/// Only one window of items is buffered: each output is available as soon
+/// as the source has produced `radius_right` items past it, and the last
+/// ones when the source ends.
+pub struct SmoothIter<I> {
+    /// `None` once the source is exhausted.
+    source: Option<I>,
+    online: OnlineFilter,
+    /// Whether the window has filled and the leading edge been queued.
+    started: bool,
+    /// Outputs computed but not yet yielded.
+    pending: VecDeque<f64>,
+}
+
+impl<I: Iterator<Item = f64>> Iterator for SmoothIter<I> {
+    type Item = f64;
+
+    fn next(&mut self) -> Option<f64> {
+        while self.pending.is_empty() {
+            let source = self.source.as_mut()?;
+            match source.next() {
+                Some(sample) => {
+                    if let Some(smoothed) = self.online.push(sample) {
+                        if !self.started {
+                            self.started = true;
+                            self.pending.extend(self.online.leading_edge());
+                        }
+                        self.pending.push_back(smoothed);
+                    }
+                }
+                None => {
+                    self.source = None;
+                    self.pending.extend(self.online.flush());
+                }
+            }
+        }
+        self.pending.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use approx::assert_relative_eq;
+
+    use super::SmoothExt;
+    use crate::Filter;
+
+    fn assert_all_float_eq(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, b) in actual.iter().zip(expected) {
+            assert_relative_eq!(a, b, epsilon = 1e-10);
+        }
+    }
+
+    #[test]
+    fn savgol_smooth_matches_batch() {
+        let data: Vec<f64> = (0..100).map(|x| x as f64).collect();
+        let smoothed: Vec<f64> = (0..100)
+            .map(|x| x as f64)
+            .savgol_smooth(Filter::new(3, 2, 0))
+            .collect();
+        assert_all_float_eq(&smoothed, &Filter::new(3, 2, 0).smooth(&data));
+    }
+
+    #[test]
+    fn savgol_smooth_nonlinear_and_short_inputs() {
+        for len in [0, 1, 2, 4, 7, 30] {
+            let data: Vec<f64> = (0..len).map(|x| (x as f64 * 0.8).sin()).collect();
+            let smoothed: Vec<f64> = data
+                .iter()
+                .copied()
+                .savgol_smooth(Filter::new(3, 2, 1))
+                .collect();
+            assert_all_float_eq(&smoothed, &Filter::new(3, 2, 1).smooth(&data));
+        }
+    }
+
+    #[test]
+    fn savgol_smooth_is_lazy() {
+        let mut pulled = 0;
+        let mut smoothed = (0..)
+            .map(|x| {
+                pulled += 1;
+                x as f64
+            })
+            .savgol_smooth(Filter::new(2, 1, 0));
+        assert_relative_eq!(smoothed.next().unwrap(), 0.0, epsilon = 1e-10);
+        assert_relative_eq!(smoothed.next().unwrap(), 1.0, epsilon = 1e-10);
+        assert_relative_eq!(smoothed.next().unwrap(), 2.0, epsilon = 1e-10);
+        drop(smoothed);
+        assert_eq!(pulled, 5);
+    }
+}
@@ -1,4 +1,71 @@
+//! Savitzky–Golay smoothing and differentiation.
+//!
+//! Everything is available from the crate root:
+//!
+//! ```
+//! use savgol::Filter;
+//!
+//! // A 5-point quadratic smoother reproduces a parabola exactly.
+//! let filter = Filter::new(2, 2, 0);
+//! let data: Vec<f64> = (0..8).map(|x| (x * x) as f64).collect();
+//! let smoothed = filter.smooth(&data);
+//! assert!((smoothed[3] - 9.0).abs() < 1e-10);
+//!
+//! // The middle weight of the same filter, from the Gram polynomials.
+//! assert!((savgol::weights(0, 2, 2, 0, 0) - 17.0 / 35.0).abs() < 1e-10);
+//! ```
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "ndarray")]
+mod array;
+mod banded;
+mod builder;
+mod cached;
+#[cfg(feature = "num-complex")]
+mod complex;
+#[cfg(feature = "high-precision")]
+mod double_double;
+mod edge;
+mod error;
+mod fallback;
+#[cfg(feature = "cffi")]
+mod ffi;
 mod filter;
-mod math;
+mod filter2d;
+mod iter;
+mod linalg;
+pub mod math;
+mod online;
+mod peaks;
+mod precomputed;
+#[cfg(feature = "python")]
+mod python;
+mod scipy;
+#[cfg(feature = "polars")]
+mod series;
+#[cfg(feature = "simd")]
+mod simd;
+mod standard;
+#[cfg(feature = "wasm")]
+mod wasm;
 
-pub use filter::Filter;
+pub use banded::BandedMatrix;
+pub use builder::FilterBuilder;
+pub use cached::CachedFilter;
+pub use edge::EdgeMode;
+pub use error::FilterError;
+pub use fallback::FallbackPolicy;
+pub use filter::{apply_kernel, savgol, Filter};
+pub use filter2d::Filter2D;
+pub use iter::{SmoothExt, SmoothIter};
+pub use math::{
+    condition_number, condition_number_chebyshev, vandermonde, weights, weights_chebyshev,
+};
+pub use online::OnlineFilter;
+pub use peaks::{peaks, peaks_above};
+pub use precomputed::PrecomputedFilter;
+pub use scipy::savgol_coeffs;
+pub use standard::standard_coefficients;
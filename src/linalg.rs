@@ -0,0 +1,98 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+// Test builds link std, whose inherent float methods make this unused.
+#[cfg(not(any(feature = "std", test)))]
+use num_traits::Float;
+
+/// Eigenvalues of a symmetric matrix, found with cyclic Jacobi rotations.
+pub fn symmetric_eigenvalues(mut a: Vec<Vec<f64>>) -> Vec<f64> {
+    let n = a.len();
+    for _ in 0..100 {
+        let off_diagonal: f64 = (0..n)
+            .flat_map(|p| (p + 1..n).map(move |q| (p, q)))
+            .map(|(p, q)| a[p][q] * a[p][q])
+            .sum();
+        let diagonal: f64 = (0..n).map(|p| a[p][p] * a[p][p]).sum();
+        if off_diagonal <= f64::EPSILON * f64::EPSILON * diagonal {
+            break;
+        }
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[p][q] == 0.0 {
+                    continue;
+                }
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for row in a.iter_mut() {
+                    let (akp, akq) = (row[p], row[q]);
+                    row[p] = c * akp - s * akq;
+                    row[q] = s * akp + c * akq;
+                }
+                let (upper, lower) = a.split_at_mut(q);
+                for (apk, aqk) in upper[p].iter_mut().zip(lower[0].iter_mut()) {
+                    let (old_p, old_q) = (*apk, *aqk);
+                    *apk = c * old_p - s * old_q;
+                    *aqk = s * old_p + c * old_q;
+                }
+            }
+        }
+    }
+    (0..n).map(|p| a[p][p]).collect()
+}
+
+/// Ratio of the largest to the smallest eigenvalue magnitude of a symmetric
+/// matrix, i.e. its 2-norm condition number.
+pub fn condition_number(a: Vec<Vec<f64>>) -> f64 {
+    let eigenvalues = symmetric_eigenvalues(a);
+    let max = eigenvalues.iter().fold(0.0_f64, |acc, e| acc.max(e.abs()));
+    let min = eigenvalues
+        .iter()
+        .fold(f64::INFINITY, |acc, e| acc.min(e.abs()));
+    max / min
+}
+
+/// Normal-equation matrix `AᵀA` for a design matrix `A` given row by row.
+pub fn normal_matrix(rows: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let cols = rows.first().map_or(0, Vec::len);
+    let mut normal = vec![vec![0.0; cols]; cols];
+    for row in rows {
+        for p in 0..cols {
+            for q in 0..cols {
+                normal[p][q] += row[p] * row[q];
+            }
+        }
+    }
+    normal
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    fn assert_float_eq(a: f64, b: f64) {
+        assert_relative_eq!(a, b, epsilon = 1e-10);
+    }
+
+    #[test]
+    fn eigenvalues_2x2() {
+        let mut eigenvalues = symmetric_eigenvalues(vec![vec![2.0, 1.0], vec![1.0, 2.0]]);
+        eigenvalues.sort_by(f64::total_cmp);
+        assert_float_eq(eigenvalues[0], 1.0);
+        assert_float_eq(eigenvalues[1], 3.0);
+    }
+
+    #[test]
+    fn condition_number_of_diagonal() {
+        let a = vec![
+            vec![4.0, 0.0, 0.0],
+            vec![0.0, 2.0, 0.0],
+            vec![0.0, 0.0, 0.5],
+        ];
+        assert_float_eq(condition_number(a), 8.0);
+    }
+}
@@ -0,0 +1,136 @@
+//! Command-line Savitzky–Golay smoothing. Reads one number per line, or
+//! comma-separated rows with `--column`, from a file or stdin and writes the
+//! smoothed series to stdout, one value per line.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::process::ExitCode;
+use std::{env, fs};
+
+use savgol::{EdgeMode, FilterBuilder};
+
+const USAGE: &str = "\
+usage: savgol [options] [FILE]
+
+Smooths the numbers in FILE (or stdin), one per line or comma-separated.
+
+options:
+  --radius R      window of 2R+1 points (default 2)
+  --degree D      polynomial degree (default 2)
+  --deriv S       derivative order (default 0)
+  --delta H       sample spacing for derivatives (default 1)
+  --edge MODE     interp, mirror, nearest, wrap or constant:C (default interp)
+  --column N      zero-based CSV column to read (default 0)
+  -h, --help      print this message";
+
+struct Options {
+    builder: FilterBuilder,
+    column: usize,
+    path: Option<String>,
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("{flag} needs a value"))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for {flag}: {value}"))
+}
+
+fn parse_edge(value: &str) -> Result<EdgeMode, String> {
+    match value {
+        "interp" => Ok(EdgeMode::Interp),
+        "mirror" => Ok(EdgeMode::Mirror),
+        "nearest" => Ok(EdgeMode::Nearest),
+        "wrap" => Ok(EdgeMode::Wrap),
+        _ => match value.strip_prefix("constant:").map(str::parse) {
+            Some(Ok(c)) => Ok(EdgeMode::Constant(c)),
+            _ => Err(format!("invalid value for --edge: {value}")),
+        },
+    }
+}
+
+/// `Ok(None)` means help was requested.
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut builder = FilterBuilder::new().radius(2);
+    let mut column = 0;
+    let mut path = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--radius" => builder = builder.radius(parse_value(&arg, args.next())?),
+            "--degree" => builder = builder.degree(parse_value(&arg, args.next())?),
+            "--deriv" => builder = builder.derivative(parse_value(&arg, args.next())?),
+            "--delta" => builder = builder.delta(parse_value(&arg, args.next())?),
+            "--edge" => {
+                let value: String = parse_value(&arg, args.next())?;
+                builder = builder.edge_mode(parse_edge(&value)?);
+            }
+            "--column" => column = parse_value(&arg, args.next())?,
+            _ if arg.starts_with('-') && arg != "-" => {
+                return Err(format!("unknown option: {arg}"))
+            }
+            _ if path.is_some() => return Err(format!("unexpected argument: {arg}")),
+            _ => path = Some(arg),
+        }
+    }
+    Ok(Some(Options {
+        builder,
+        column,
+        path,
+    }))
+}
+
+/// Reads `column` of every nonempty line. A first line that does not parse
+/// is taken to be a header and skipped.
+fn read_column(input: impl BufRead, column: usize) -> Result<Vec<f64>, String> {
+    let mut values = Vec::new();
+    for (index, line) in input.lines().enumerate() {
+        let line = line.map_err(|e| e.to_string())?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let field = line
+            .split(',')
+            .nth(column)
+            .ok_or_else(|| format!("line {}: no column {column}", index + 1))?
+            .trim();
+        match field.parse() {
+            Ok(value) => values.push(value),
+            Err(_) if index == 0 => {}
+            Err(_) => return Err(format!("line {}: not a number: {field}", index + 1)),
+        }
+    }
+    Ok(values)
+}
+
+fn run(options: Options) -> Result<(), String> {
+    let filter = options.builder.build().map_err(|e| e.to_string())?;
+    let input: Box<dyn Read> = match options.path.as_deref() {
+        None | Some("-") => Box::new(io::stdin()),
+        Some(path) => Box::new(fs::File::open(path).map_err(|e| format!("{path}: {e}"))?),
+    };
+    let data = read_column(BufReader::new(input), options.column)?;
+
+    let mut out = io::BufWriter::new(io::stdout().lock());
+    for value in filter.smooth(&data) {
+        writeln!(out, "{value}").map_err(|e| e.to_string())?;
+    }
+    out.flush().map_err(|e| e.to_string())
+}
+
+fn main() -> ExitCode {
+    let result = match parse_args(env::args().skip(1)) {
+        Ok(Some(options)) => run(options),
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(message) => Err(format!("{message}\n\n{USAGE}")),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("savgol: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
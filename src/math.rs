@@ -1,40 +1,577 @@
+//! The Gram polynomials and least-squares weights behind the filters, for
+//! building other discrete least-squares tools on the same basis.
+
 // Reference: A., Gorry (1990). "General least-squares smoothing and differentiation by the convolution (Savitzky–Golay) method". Analytical Chemistry. 62 (6): 570–3. doi:10.1021/ac00205a007.
+//
+// This is the crate's only implementation of the Gram polynomials and
+// weights; everything else calls into it. Generalized factorials are
+// computed as differences of ln factorials rather than as direct products,
+// which overflow f64 once the window exceeds about 170 points.
+
+#[cfg(test)]
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use num_traits::Float;
+
+#[cfg(feature = "high-precision")]
+use crate::double_double::DoubleDouble;
+use crate::linalg;
+
+/// The generalized factorial `a^(b) = (a)(a-1)...(a-b+1)`, the product of
+/// the `b` integers counting down from `a`, for `a, b >= 0`. It is 1 when
+/// `b == 0` and 0 when `b > a`.
+///
+/// The product overflows `f64` for large arguments; see
+/// `ln_generalized_factorial`.
+pub fn generalized_factorial(a: i64, b: i64) -> f64 {
+    (0..b).map(|j| (a - j) as f64).product()
+}
+
+/// Calculates the ln generalized factorial (a)(a-1)...(a-b+1), for
+/// `a, b >= 0`, as a difference of ln factorials, so that it stays finite
+/// where `generalized_factorial` overflows. When `b > a` the product
+/// includes the factor 0, so this is `-inf`.
+pub fn ln_generalized_factorial(a: i64, b: i64) -> f64 {
+    ln_generalized_factorial_with(a, b, ln_factorial)
+}
+
+/// `ln_generalized_factorial` with ln(n!) given by `ln_factorial`.
+fn ln_generalized_factorial_with(a: i64, b: i64, ln_factorial: impl Fn(u64) -> f64) -> f64 {
+    debug_assert!(a >= 0 && b >= 0, "invalid arguments ({}, {})", a, b);
+    if a < b {
+        return f64::NEG_INFINITY;
+    }
+    ln_factorial(a as u64) - ln_factorial((a - b) as u64)
+}
+
+/// ln(n!), from `statrs` when `std` is available.
+#[cfg(feature = "std")]
+fn ln_factorial(n: u64) -> f64 {
+    statrs::function::factorial::ln_factorial(n)
+}
+
+#[cfg(not(feature = "std"))]
+fn ln_factorial(n: u64) -> f64 {
+    ln_factorial_fallback(n)
+}
+
+/// ln(n!) without `statrs`: the ln of the exact product up to 170! (the
+/// largest factorial finite in `f64`), computed as `statrs` does, and
+/// Stirling's series for ln Γ(n + 1) beyond.
+#[cfg(any(not(feature = "std"), test))]
+fn ln_factorial_fallback(n: u64) -> f64 {
+    if n <= 170 {
+        return (2..=n).fold(1.0, |product: f64, k| product * k as f64).ln();
+    }
+    let x = (n + 1) as f64;
+    let x2 = x * x;
+    let series = 1.0 / 12.0 - (1.0 / 360.0 - (1.0 / 1260.0 - 1.0 / (1680.0 * x2)) / x2) / x2;
+    (x - 0.5) * x.ln() - x + 0.5 * (2.0 * core::f64::consts::PI).ln() + series / x
+}
+
+/// A running sum with Neumaier's compensation, which tracks the low-order
+/// bits lost to rounding in each addition and adds them back at the end.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CompensatedSum<T = f64> {
+    sum: T,
+    compensation: T,
+}
+
+impl<T: Float> Default for CompensatedSum<T> {
+    fn default() -> Self {
+        CompensatedSum {
+            sum: T::zero(),
+            compensation: T::zero(),
+        }
+    }
+}
+
+impl<T: Float> CompensatedSum<T> {
+    pub(crate) fn add(&mut self, value: T) {
+        let total = self.sum + value;
+        self.compensation = self.compensation
+            + if self.sum.abs() >= value.abs() {
+                (self.sum - total) + value
+            } else {
+                (value - total) + self.sum
+            };
+        self.sum = total;
+    }
 
-/// Calculates the ln generalized factorial (a)(a-1)...(a-b+1)
-fn ln_generalized_factorial(a: i64, b: i64) -> f64 {
-    statrs::function::factorial::ln_factorial(a as u64)
-        - statrs::function::factorial::ln_factorial((a - b) as u64)
+    pub(crate) fn value(self) -> T {
+        self.sum + self.compensation
+    }
 }
 
+/// Memoized Gram polynomial values keyed by `(2i, 2m, k, s)`.
+#[cfg(test)]
+type GramCache = BTreeMap<(i64, i64, i64, i64), f64>;
+
 /// Calculates the Gram Polynomial (s=0), or it's s'th derivative
 /// evaluated at i, order k, over 2m+1 points.
-fn gram_poly(i: i64, m: i64, k: i64, s: i64) -> f64 {
+///
+/// Positions are given doubled, as `i2 = 2i` and `two_m = 2m`, so that
+/// windows with an even number of points (half-integer m and i) can be
+/// represented exactly.
+///
+/// Recursive calls are memoized in `cache`. Without this the recursion
+/// branches three ways per order and grows exponentially in k. The weights
+/// use `gram_poly_table` instead, which this is kept as a reference for.
+#[cfg(test)]
+fn gram_poly_memo(i2: i64, two_m: i64, k: i64, s: i64, cache: &mut GramCache) -> f64 {
     if k == 0 && s == 0 {
         return 1.0;
     }
     if k <= 0 {
         return 0.0;
     }
+    if let Some(&value) = cache.get(&(i2, two_m, k, s)) {
+        return value;
+    }
+
+    // The lower derivative is scaled by s, so it can be skipped when s is 0.
+    let lower_derivative = if s > 0 {
+        gram_poly_memo(i2, two_m, k - 1, s - 1, cache) * s as f64
+    } else {
+        0.0
+    };
+    let part1 = (4 * k - 2) as f64 / (k * (two_m - k + 1)) as f64
+        * (gram_poly_memo(i2, two_m, k - 1, s, cache) * (i2 as f64 / 2.0) + lower_derivative);
+    let part2 = ((k - 1) * (two_m + k)) as f64 / (k * (two_m - k + 1)) as f64
+        * gram_poly_memo(i2, two_m, k - 2, s, cache);
+    let value = part1 - part2;
+    cache.insert((i2, two_m, k, s), value);
+    value
+}
+
+/// The Gram polynomial of order `k` over the `2m + 1` points
+/// `-m..=m`, or its `s`'th derivative, evaluated at `i`.
+///
+/// These are the polynomials orthogonal over the sample points (Gorry 1990),
+/// normalized to 1 at the end of the window, `i = m`: `P_0 = 1`,
+/// `P_1(i) = i / m`, and so on. Any `i` may be given, including points outside the
+/// window, and `k` runs up to `2m`. The result is 0 for negative `k` or
+/// `s`, and for `s > k`, where the derivative vanishes.
+///
+/// ```
+/// use savgol::math::gram_poly;
+///
+/// let m = 4;
+/// for j in 0..=4 {
+///     for k in 0..j {
+///         let dot: f64 = (-m..=m).map(|i| gram_poly(i, m, j, 0) * gram_poly(i, m, k, 0)).sum();
+///         assert!(dot.abs() < 1e-12);
+///     }
+/// }
+/// assert_eq!(gram_poly(3, m, 1, 0), 0.75);
+/// assert_eq!(gram_poly(3, m, 1, 1), 0.25);
+/// ```
+pub fn gram_poly(i: i64, m: i64, k: i64, s: i64) -> f64 {
+    if k < 0 || s < 0 || s > k {
+        return 0.0;
+    }
+    gram_poly_table(2 * i, 2 * m, k, s)[k as usize][s as usize]
+}
+
+/// The Gram polynomials of orders `0..=n` and their derivatives `0..=s_max`
+/// at `i2`, as `table[k][s]`, over the window described by `two_m` (both
+/// doubled as in `gram_poly_memo`).
+///
+/// Filled bottom-up over k with the same recurrence and arithmetic as
+/// `gram_poly_memo`, so the values are identical, but without recursion or
+/// a cache.
+fn gram_poly_table(i2: i64, two_m: i64, n: i64, s_max: i64) -> Vec<Vec<f64>> {
+    let width = s_max as usize + 1;
+    let mut table: Vec<Vec<f64>> = Vec::with_capacity(n as usize + 1);
+    let mut first = vec![0.0; width];
+    first[0] = 1.0;
+    table.push(first);
+    let zeros = vec![0.0; width];
+    for k in 1..=n {
+        let two_below = if k >= 2 {
+            &table[k as usize - 2]
+        } else {
+            &zeros
+        };
+        let mut row = vec![0.0; width];
+        gram_poly_step(i2, two_m, k, &table[k as usize - 1], two_below, &mut row);
+        table.push(row);
+    }
+    table
+}
+
+/// One step of the recurrence in `gram_poly_table`: fills `row` with the
+/// derivatives `0..row.len()` of the order `k` polynomial from those of
+/// orders `k - 1` (`below`) and `k - 2` (`two_below`).
+fn gram_poly_step(i2: i64, two_m: i64, k: i64, below: &[f64], two_below: &[f64], row: &mut [f64]) {
+    for (s, value) in row.iter_mut().enumerate() {
+        let lower_derivative = if s > 0 { below[s - 1] * s as f64 } else { 0.0 };
+        let part1 = (4 * k - 2) as f64 / (k * (two_m - k + 1)) as f64
+            * (below[s] * (i2 as f64 / 2.0) + lower_derivative);
+        let part2 = ((k - 1) * (two_m + k)) as f64 / (k * (two_m - k + 1)) as f64 * two_below[s];
+        *value = part1 - part2;
+    }
+}
+
+/// `weights_doubled` without heap allocation, for `s < N`: the Gram
+/// polynomials are carried two orders at a time in arrays of length `N`,
+/// and each normalizing factor is taken straight from `ln_factorial`.
+pub(crate) fn weight_doubled_on_stack<const N: usize>(
+    i2: i64,
+    two_m: i64,
+    n: i64,
+    t2: i64,
+    s: i64,
+) -> f64 {
+    assert!((s as usize) < N);
+    let width = s as usize + 1;
+    // Orders k - 2, k - 1, and k, at i (value only) and at t.
+    let (mut i_two_below, mut i_below, mut i_current) = ([0.0], [1.0], [0.0]);
+    let (mut t_two_below, mut t_below, mut t_current) = ([0.0; N], [0.0; N], [0.0; N]);
+    t_below[0] = 1.0;
+    let mut sum = CompensatedSum::<f64>::default();
+    for k in 0..=n {
+        if k > 0 {
+            gram_poly_step(i2, two_m, k, &i_below, &i_two_below, &mut i_current);
+            gram_poly_step(
+                t2,
+                two_m,
+                k,
+                &t_below[..width],
+                &t_two_below[..width],
+                &mut t_current[..width],
+            );
+            (i_two_below, i_below) = (i_below, i_current);
+            (t_two_below, t_below) = (t_below, t_current);
+        }
+        let factor = (2 * k + 1) as f64
+            * (ln_generalized_factorial_with(two_m, k, ln_factorial)
+                - ln_generalized_factorial_with(two_m + k + 1, k + 1, ln_factorial))
+            .exp();
+        sum.add(factor * i_below[0] * t_below[s as usize]);
+    }
+    sum.value()
+}
 
-    let part1 = (4 * k - 2) as f64 / (k * (2 * m - k + 1)) as f64
-        * (gram_poly(i, m, k - 1, s) * i as f64 + gram_poly(i, m, k - 1, s - 1) * s as f64);
-    let part2 =
-        ((k - 1) * (2 * m + k)) as f64 / (k * (2 * m - k + 1)) as f64 * gram_poly(i, m, k - 2, s);
-    part1 - part2
+/// `weights_doubled` in double-double arithmetic throughout: the Gram
+/// recurrence, and normalizing factors built up as exact ratios of
+/// products, `(2m)^(k) / (2m + k + 1)^(k+1) = (2m)^(k-1) / (2m + k)^(k) *
+/// (2m - k + 1) / (2m + k + 1)`, instead of from ln factorials.
+#[cfg(feature = "high-precision")]
+pub(crate) fn weights_doubled_dd(i2: i64, two_m: i64, n: i64, t2: i64, s: i64) -> DoubleDouble {
+    let dd = |x: i64| DoubleDouble::from(x as f64);
+    let width = s as usize + 1;
+    let gram = |x2: i64| {
+        let x = dd(x2) / dd(2);
+        let mut table: Vec<Vec<DoubleDouble>> = Vec::with_capacity(n as usize + 1);
+        let mut first = vec![DoubleDouble::ZERO; width];
+        first[0] = dd(1);
+        table.push(first);
+        for k in 1..=n {
+            let a = dd(4 * k - 2) / dd(k * (two_m - k + 1));
+            let b = dd((k - 1) * (two_m + k)) / dd(k * (two_m - k + 1));
+            let row = (0..width)
+                .map(|j| {
+                    let below = table[k as usize - 1][j];
+                    let lower_derivative = if j > 0 {
+                        table[k as usize - 1][j - 1] * dd(j as i64)
+                    } else {
+                        DoubleDouble::ZERO
+                    };
+                    let two_below = if k >= 2 {
+                        table[k as usize - 2][j]
+                    } else {
+                        DoubleDouble::ZERO
+                    };
+                    a * (below * x + lower_derivative) - b * two_below
+                })
+                .collect();
+            table.push(row);
+        }
+        table
+    };
+    let (at_i, at_t) = (gram(i2), gram(t2));
+    let mut ratio = dd(1) / dd(two_m + 1);
+    let mut sum = DoubleDouble::ZERO;
+    for k in 0..=n {
+        if k > 0 {
+            ratio = ratio * dd(two_m - k + 1) / dd(two_m + k + 1);
+        }
+        sum = sum + dd(2 * k + 1) * ratio * at_i[k as usize][0] * at_t[k as usize][s as usize];
+    }
+    sum
 }
 
 /// Calculates the weight of the i'th data point for the t'th Least-Square
 /// point of the s'th derivative, over 2m+1 points, order n.
+///
+/// For `s > 0` the weights give the s'th derivative of the fit itself per
+/// unit sample spacing: the derivatives of the Gram polynomials already
+/// carry the `s!` that separates it from the coefficient of `(x - t)^s`.
+/// For samples `delta` apart, divide by `delta^s`.
+///
+/// For `s > n` every weight is exactly zero, since the s'th derivative of
+/// a polynomial of degree n vanishes. `Filter::try_new` rejects such
+/// filters with `FilterError::DerivativeExceedsDegree` rather than
+/// silently returning zeros.
 pub fn weights(i: i64, m: i64, n: i64, t: i64, s: i64) -> f64 {
-    let mut sum = 0.0;
+    weights_doubled(2 * i, 2 * m, n, 2 * t, s)
+}
+
+/// `weights` with the positions doubled, as `i2 = 2i`, `two_m = 2m`, and
+/// `t2 = 2t`. An odd `two_m` describes a window with an even number of
+/// points, whose positions `i` are then half-integers.
+pub(crate) fn weights_doubled(i2: i64, two_m: i64, n: i64, t2: i64, s: i64) -> f64 {
+    weights_doubled_orders(i2, two_m, n, t2, &[s])[0]
+}
+
+/// `weights_doubled` for several derivative orders at once, sharing the
+/// Gram polynomials at `i2`, which do not depend on the order.
+pub(crate) fn weights_doubled_orders(
+    i2: i64,
+    two_m: i64,
+    n: i64,
+    t2: i64,
+    orders: &[i64],
+) -> Vec<f64> {
+    WeightTable::new(two_m, n).weights(i2, t2, orders)
+}
+
+/// Everything in `weights_doubled` that depends only on the window and
+/// degree, computed once so that many weights for the same filter can share
+/// it: the normalizing factor of each Gram polynomial, from a table of ln
+/// factorials up to `two_m + n + 1`.
+pub(crate) struct WeightTable {
+    two_m: i64,
+    /// `(2k + 1) (2m)^(k) / (2m + k + 1)^(k+1)` for `k = 0..=n`, with
+    /// generalized factorials `a^(b) = a (a - 1) ... (a - b + 1)`.
+    factors: Vec<f64>,
+}
+
+#[cfg(all(test, feature = "std"))]
+std::thread_local! {
+    /// How many `WeightTable`s this thread has built, for tests asserting
+    /// that a cached path does no weight computation.
+    pub(crate) static TABLES_BUILT: core::cell::Cell<usize> = const { core::cell::Cell::new(0) };
+}
+
+impl WeightTable {
+    pub(crate) fn new(two_m: i64, n: i64) -> Self {
+        #[cfg(all(test, feature = "std"))]
+        TABLES_BUILT.with(|built| built.set(built.get() + 1));
+        let ln_factorials: Vec<f64> = (0..=(two_m + n + 1) as u64).map(ln_factorial).collect();
+        let ln_generalized =
+            |a, b| ln_generalized_factorial_with(a, b, |x| ln_factorials[x as usize]);
+        let factors = (0..=n)
+            .map(|k| {
+                (2 * k + 1) as f64
+                    * (ln_generalized(two_m, k) - ln_generalized(two_m + k + 1, k + 1)).exp()
+            })
+            .collect();
+        WeightTable { two_m, factors }
+    }
+
+    /// `weights_doubled(i2, two_m, n, t2, s)` for each `s` in `orders`.
+    pub(crate) fn weights(&self, i2: i64, t2: i64, orders: &[i64]) -> Vec<f64> {
+        let n = self.factors.len() as i64 - 1;
+        let s_max = orders.iter().copied().max().unwrap_or(0);
+        let at_i = gram_poly_table(i2, self.two_m, n, 0);
+        let at_t = gram_poly_table(t2, self.two_m, n, s_max);
+        let mut sums = vec![CompensatedSum::<f64>::default(); orders.len()];
+        for ((factor, at_i), at_t) in self.factors.iter().zip(&at_i).zip(&at_t) {
+            let common = factor * at_i[0];
+            for (sum, &s) in sums.iter_mut().zip(orders) {
+                sum.add(common * at_t[s as usize]);
+            }
+        }
+        sums.into_iter().map(CompensatedSum::value).collect()
+    }
+
+    /// The terms of `weights(i2, t2, &[s])`, one per Gram polynomial order
+    /// `k = 0..=n`: the weight is their sum. Products of these terms along
+    /// two axes give the weights of a fit in two dimensions.
+    pub(crate) fn terms(&self, i2: i64, t2: i64, s: i64) -> Vec<f64> {
+        let n = self.factors.len() as i64 - 1;
+        let at_i = gram_poly_table(i2, self.two_m, n, 0);
+        let at_t = gram_poly_table(t2, self.two_m, n, s);
+        self.factors
+            .iter()
+            .zip(&at_i)
+            .zip(&at_t)
+            .map(|((factor, at_i), at_t)| factor * at_i[0] * at_t[s as usize])
+            .collect()
+    }
+}
+
+/// Weights of each point at positions `xs` for the s'th derivative at `t` of
+/// a degree n weighted least-squares fit, where point j counts with weight
+/// `point_weights[j]`.
+///
+/// This generalizes `weights` to arbitrary positions and weights, building
+/// the polynomials orthogonal under the weighted inner product with the
+/// three-term recurrence of Forsythe (1957) in place of the Gram
+/// polynomials. Returns `None` if fewer than n + 1 points have nonzero
+/// weight, since the fit is then underdetermined.
+pub(crate) fn weights_weighted(
+    xs: &[f64],
+    point_weights: &[f64],
+    n: i64,
+    t: f64,
+    s: i64,
+) -> Option<Vec<f64>> {
+    if point_weights.iter().filter(|&&w| w != 0.0).count() <= n as usize {
+        return None;
+    }
+    // Scale positions into [-1, 1] to keep the recurrence well conditioned.
+    let scale = xs.iter().fold(0.0_f64, |max, x| max.max(x.abs())).max(1.0);
+    let xs: Vec<f64> = xs.iter().map(|x| x / scale).collect();
+    let t = t / scale;
+    let s = s as usize;
+
+    // Values of the previous and current polynomial at each point, and
+    // their derivatives 0..=s at t.
+    let mut previous = vec![0.0; xs.len()];
+    let mut current = vec![1.0; xs.len()];
+    let mut previous_at_t = vec![0.0; s + 1];
+    let mut current_at_t = vec![0.0; s + 1];
+    current_at_t[0] = 1.0;
+    let mut previous_norm = 1.0;
+    let mut result = vec![0.0; xs.len()];
     for k in 0..=n {
-        sum += (2 * k + 1) as f64
-            * (ln_generalized_factorial(2 * m, k) - ln_generalized_factorial(2 * m + k + 1, k + 1))
-                .exp()
-            * gram_poly(i, m, k, 0)
-            * gram_poly(t, m, k, s);
+        let norm: f64 = point_weights
+            .iter()
+            .zip(&current)
+            .map(|(w, p)| w * p * p)
+            .sum();
+        for (j, value) in result.iter_mut().enumerate() {
+            *value += point_weights[j] * current[j] * current_at_t[s] / norm;
+        }
+        if k == n {
+            break;
+        }
+        let a = point_weights
+            .iter()
+            .zip(&current)
+            .zip(&xs)
+            .map(|((w, p), x)| w * x * p * p)
+            .sum::<f64>()
+            / norm;
+        let b = if k == 0 { 0.0 } else { norm / previous_norm };
+        let next: Vec<f64> = (0..xs.len())
+            .map(|j| (xs[j] - a) * current[j] - b * previous[j])
+            .collect();
+        let next_at_t: Vec<f64> = (0..=s)
+            .map(|d| {
+                let lower = if d > 0 {
+                    d as f64 * current_at_t[d - 1]
+                } else {
+                    0.0
+                };
+                (t - a) * current_at_t[d] + lower - b * previous_at_t[d]
+            })
+            .collect();
+        previous = core::mem::replace(&mut current, next);
+        previous_at_t = core::mem::replace(&mut current_at_t, next_at_t);
+        previous_norm = norm;
     }
-    sum
+    for value in &mut result {
+        *value /= scale.powi(s as i32);
+    }
+    Some(result)
+}
+
+/// Positions of the 2m+1 Chebyshev nodes of the first kind, scaled to span
+/// the window -m..=m and sorted in ascending order.
+fn chebyshev_nodes(m: i64) -> Vec<f64> {
+    let count = (2 * m + 1) as f64;
+    (0..=2 * m)
+        .map(|j| -(m as f64) * (core::f64::consts::PI * (2 * j + 1) as f64 / (2.0 * count)).cos())
+        .collect()
+}
+
+/// Evaluates the s'th derivative of the Chebyshev polynomials T_0..=T_n at x.
+fn chebyshev_poly(x: f64, n: i64, s: i64) -> Vec<f64> {
+    // table[d][k] holds the d'th derivative of T_k at x.
+    let mut table = vec![vec![0.0; (n + 1) as usize]; (s + 1) as usize];
+    for d in 0..=s as usize {
+        for k in 0..=n as usize {
+            table[d][k] = match k {
+                0 => (d == 0) as u8 as f64,
+                1 => match d {
+                    0 => x,
+                    1 => 1.0,
+                    _ => 0.0,
+                },
+                _ => {
+                    let lower = if d > 0 {
+                        2.0 * d as f64 * table[d - 1][k - 1]
+                    } else {
+                        0.0
+                    };
+                    2.0 * x * table[d][k - 1] + lower - table[d][k - 2]
+                }
+            };
+        }
+    }
+    table.swap_remove(s as usize)
+}
+
+/// Calculates the weight of the i'th data point for the t'th Least-Square
+/// point of the s'th derivative, over 2m+1 points, order n, with the samples
+/// placed on Chebyshev nodes instead of the uniform grid.
+///
+/// The fit uses Chebyshev polynomials, which are exactly orthogonal over the
+/// nodes, so the least-squares problem stays well conditioned at high degree
+/// (see `condition_number_chebyshev`). The tradeoff is that the samples are
+/// treated as if taken at the nodes, so the result only approximates the
+/// uniform-grid `weights` and no longer matches them exactly for n >= 2.
+pub fn weights_chebyshev(i: i64, m: i64, n: i64, t: i64, s: i64) -> f64 {
+    let count = (2 * m + 1) as f64;
+    let scale = m.max(1) as f64;
+    let node = chebyshev_nodes(m)[(i + m) as usize] / scale;
+    let at_node = chebyshev_poly(node, n, 0);
+    let at_t = chebyshev_poly(t as f64 / scale, n, s);
+    let mut sum = 0.0;
+    for k in 0..=n as usize {
+        let norm = if k == 0 { 1.0 } else { 2.0 };
+        sum += norm / count * at_node[k] * at_t[k];
+    }
+    sum / scale.powi(s as i32)
+}
+
+/// The `(2 * radius + 1) x (degree + 1)` design matrix of a window: row
+/// `i + radius` is `[1, i, i^2, ..., i^degree]` for `i = -radius..=radius`.
+///
+/// Solving `vandermonde(radius, degree) * a = window` in the least-squares
+/// sense gives the coefficients of the fit that `weights` evaluates, for
+/// callers who want to use a solver of their own.
+pub fn vandermonde(radius: usize, degree: u64) -> Vec<Vec<f64>> {
+    let m = radius as i64;
+    (-m..=m)
+        .map(|i| (0..=degree).map(|k| (i as f64).powi(k as i32)).collect())
+        .collect()
+}
+
+/// Condition number of the normal equations for a degree n polynomial fit on
+/// the uniform grid -m..=m, using monomials in the scaled coordinate i/m.
+pub fn condition_number(m: i64, n: i64) -> f64 {
+    let scale = m.max(1) as f64;
+    let rows: Vec<Vec<f64>> = (-m..=m)
+        .map(|i| (0..=n).map(|k| (i as f64 / scale).powi(k as i32)).collect())
+        .collect();
+    linalg::condition_number(linalg::normal_matrix(&rows))
+}
+
+/// Condition number of the normal equations behind `weights_chebyshev`.
+pub fn condition_number_chebyshev(m: i64, n: i64) -> f64 {
+    let scale = m.max(1) as f64;
+    let rows: Vec<Vec<f64>> = chebyshev_nodes(m)
+        .into_iter()
+        .map(|node| chebyshev_poly(node / scale, n, 0))
+        .collect();
+    linalg::condition_number(linalg::normal_matrix(&rows))
 }
 
 #[cfg(test)]
@@ -75,6 +612,169 @@ mod tests {
         assert_float_eq(ln_generalized_factorial(5, 1).exp(), 5.0);
     }
 
+    #[test]
+    fn weights_chebyshev_low_degree_matches_uniform() {
+        for i in -3..=3 {
+            assert_float_eq(weights_chebyshev(i, 3, 1, 0, 0), weights(i, 3, 1, 0, 0));
+            for t in -3..=3 {
+                assert_float_eq(weights_chebyshev(i, 3, 0, t, 0), weights(i, 3, 0, t, 0));
+            }
+        }
+    }
+
+    #[test]
+    fn weights_chebyshev_reproduce_polynomial_on_nodes() {
+        // The fit is exact for polynomials sampled at the nodes themselves.
+        let nodes = chebyshev_nodes(4);
+        let y = |x: f64| 1.0 - 2.0 * x + 0.5 * x * x;
+        for t in -4..=4 {
+            let value: f64 = (-4..=4)
+                .map(|i| weights_chebyshev(i, 4, 2, t, 0) * y(nodes[(i + 4) as usize]))
+                .sum();
+            let slope: f64 = (-4..=4)
+                .map(|i| weights_chebyshev(i, 4, 2, t, 1) * y(nodes[(i + 4) as usize]))
+                .sum();
+            assert_float_eq(value, y(t as f64));
+            assert_float_eq(slope, -2.0 + t as f64);
+        }
+    }
+
+    #[test]
+    fn condition_number_chebyshev_is_lower_at_high_degree() {
+        assert!(condition_number_chebyshev(10, 10) < 10.0);
+        assert!(condition_number(10, 10) > 1e4);
+        assert!(condition_number_chebyshev(10, 10) < condition_number(10, 10));
+    }
+
+    /// The original, unmemoized recursion.
+    fn gram_poly_naive(i: i64, m: i64, k: i64, s: i64) -> f64 {
+        if k == 0 && s == 0 {
+            return 1.0;
+        }
+        if k <= 0 {
+            return 0.0;
+        }
+        let part1 = (4 * k - 2) as f64 / (k * (2 * m - k + 1)) as f64
+            * (gram_poly_naive(i, m, k - 1, s) * i as f64
+                + gram_poly_naive(i, m, k - 1, s - 1) * s as f64);
+        let part2 = ((k - 1) * (2 * m + k)) as f64 / (k * (2 * m - k + 1)) as f64
+            * gram_poly_naive(i, m, k - 2, s);
+        part1 - part2
+    }
+
+    #[test]
+    fn gram_poly_memo_matches_naive_recursion() {
+        for k in 0..=8 {
+            for s in 0..=3 {
+                for i in -6..=6 {
+                    assert_float_eq(
+                        gram_poly_memo(2 * i, 12, k, s, &mut GramCache::new()),
+                        gram_poly_naive(i, 6, k, s),
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn gram_poly_table_matches_recursion_at_degree_15() {
+        for two_m in [30, 31, 40] {
+            for i2 in [-two_m, -7, 0, 3, two_m] {
+                let table = gram_poly_table(i2, two_m, 15, 4);
+                let mut cache = GramCache::new();
+                for (k, row) in table.iter().enumerate() {
+                    for (s, &value) in row.iter().enumerate() {
+                        assert_eq!(
+                            value,
+                            gram_poly_memo(i2, two_m, k as i64, s as i64, &mut cache)
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn weight_doubled_on_stack_matches_weights_doubled() {
+        for (two_m, n) in [(4, 2), (7, 3), (20, 6)] {
+            for s in 0..=n.min(3) {
+                for i2 in (-two_m..=two_m).step_by(2) {
+                    for t2 in [-two_m, 0, two_m] {
+                        assert_relative_eq!(
+                            weight_doubled_on_stack::<8>(i2, two_m, n, t2, s),
+                            weights_doubled(i2, two_m, n, t2, s),
+                            epsilon = 1e-12
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "high-precision")]
+    #[test]
+    fn weights_doubled_dd_matches_weights_doubled() {
+        for (two_m, n, s) in [(4, 2, 0), (7, 3, 1), (20, 6, 2)] {
+            for i2 in (-two_m..=two_m).step_by(2) {
+                for t2 in [-two_m, 0, two_m] {
+                    assert_relative_eq!(
+                        weights_doubled_dd(i2, two_m, n, t2, s).to_f64(),
+                        weights_doubled(i2, two_m, n, t2, s),
+                        epsilon = 1e-12
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn vandermonde_rows_are_powers() {
+        let matrix = super::vandermonde(3, 4);
+        assert_eq!(matrix.len(), 7);
+        assert_eq!(matrix[1], vec![1.0, -2.0, 4.0, -8.0, 16.0]);
+        assert_eq!(matrix[3], vec![1.0, 0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(matrix[6], vec![1.0, 3.0, 9.0, 27.0, 81.0]);
+    }
+
+    // Timing needs std's clock.
+    #[cfg(feature = "std")]
+    #[test]
+    fn weights_degree_12_over_101_points_is_fast() {
+        let start = std::time::Instant::now();
+        let weight_sum: f64 = (-50..=50)
+            .flat_map(|t| (-50..=50).map(move |i| weights(i, 50, 12, t, 0)))
+            .sum();
+        // Each of the 101 rows sums to 1.
+        assert_relative_eq!(weight_sum, 101.0, epsilon = 1e-6);
+        assert!(start.elapsed() < std::time::Duration::from_secs(10));
+    }
+
+    #[test]
+    fn weights_derivative_above_degree_are_zero() {
+        for i in -3..=3 {
+            for t in -3..=3 {
+                assert_eq!(weights(i, 3, 2, t, 3), 0.0);
+                assert_eq!(weights(i, 3, 1, t, 4), 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn weights_doubled_even_window_is_exact_for_cubic() {
+        // Four points at i = -1.5, -0.5, 0.5, 1.5, evaluated at the center.
+        let y = |x: f64| 2.0 - x + 0.5 * x * x - 0.25 * x * x * x;
+        let value: f64 = [-3, -1, 1, 3]
+            .iter()
+            .map(|&i2| weights_doubled(i2, 3, 3, 0, 0) * y(i2 as f64 / 2.0))
+            .sum();
+        assert_float_eq(value, 2.0);
+        let mean: f64 = [-3, -1, 1, 3]
+            .iter()
+            .map(|&i2| weights_doubled(i2, 3, 0, 0, 0))
+            .sum();
+        assert_float_eq(mean, 1.0);
+    }
+
     #[test]
     fn weight_5pt_quadratic_t_0() {
         assert_float_eq(weights(-2, 2, 2, 0, 0), -3.0 / 35.0);
@@ -154,4 +854,137 @@ mod tests {
         assert_float_eq(weights(1, 2, 3, -2, 0), 4.0 / 70.0);
         assert_float_eq(weights(2, 2, 3, -2, 0), -1.0 / 70.0);
     }
+
+    #[test]
+    fn weights_weighted_uniform_matches_gram() {
+        let xs: Vec<f64> = (-3..=3).map(|i| i as f64).collect();
+        let ones = vec![1.0; 7];
+        for (n, t, s) in [(2, 0, 0), (3, -2, 1), (4, 3, 2)] {
+            let weighted = super::weights_weighted(&xs, &ones, n, t as f64, s).unwrap();
+            for (i, w) in (-3..=3).zip(weighted) {
+                assert_float_eq(w, super::weights(i, 3, n, t, s));
+            }
+        }
+    }
+
+    #[test]
+    fn weights_weighted_needs_enough_points() {
+        let xs = [-1.0, 0.0, 1.0, 2.0];
+        assert!(super::weights_weighted(&xs, &[1.0, 0.0, 0.0, 1.0], 2, 0.0, 0).is_none());
+        assert!(super::weights_weighted(&xs, &[1.0, 0.0, 1.0, 1.0], 2, 0.0, 0).is_some());
+    }
+
+    #[test]
+    fn compensated_sum_keeps_small_terms() {
+        let mut sum = super::CompensatedSum::<f64>::default();
+        for value in [1.0, 1e100, 1.0, -1e100] {
+            sum.add(value);
+        }
+        assert_eq!(sum.value(), 2.0);
+    }
+
+    #[test]
+    fn gram_poly_out_of_range_orders_are_zero() {
+        assert_eq!(gram_poly(2, 4, -1, 0), 0.0);
+        assert_eq!(gram_poly(2, 4, 2, -1), 0.0);
+        assert_eq!(gram_poly(2, 4, 2, 3), 0.0);
+        assert_eq!(gram_poly(2, 4, 0, 0), 1.0);
+        assert_eq!(gram_poly(2, 4, 1, 1), 0.25);
+    }
+
+    #[test]
+    fn long_window_sum_spanning_orders_of_magnitude() {
+        // A cubic reaching 1.25e8 at the ends of a 1001-point window, whose
+        // fit at the center is exactly 1: the terms cancel almost entirely.
+        let data: Vec<f64> = (0..=1000)
+            .map(|x| (x as f64 - 500.0).powi(3) + 1.0)
+            .collect();
+        let filter = crate::Filter::new(500, 3, 0);
+        let smoothed = filter.smooth(&data)[500];
+        let naive: f64 = filter
+            .coefficients()
+            .iter()
+            .zip(&data)
+            .map(|(w, x)| w * x)
+            .sum();
+        assert!((smoothed - 1.0).abs() < 1e-9);
+        assert!((smoothed - 1.0).abs() * 10.0 < (naive - 1.0).abs());
+    }
+
+    #[test]
+    fn ln_generalized_factorial_at_boundary() {
+        // a == b is the full factorial, including the empty product 0! = 1.
+        assert_float_eq(super::ln_generalized_factorial(3, 3).exp(), 6.0);
+        assert_float_eq(super::ln_generalized_factorial(0, 0).exp(), 1.0);
+    }
+
+    #[test]
+    fn ln_generalized_factorial_b_exceeds_a_is_zero_product() {
+        assert_eq!(super::ln_generalized_factorial(3, 4), f64::NEG_INFINITY);
+        assert_eq!(super::ln_generalized_factorial(0, 7).exp(), 0.0);
+    }
+
+    #[test]
+    fn generalized_factorial_matches_ln() {
+        assert_eq!(super::generalized_factorial(5, 0), 1.0);
+        assert_eq!(super::generalized_factorial(5, 2), 20.0);
+        assert_eq!(super::generalized_factorial(3, 4), 0.0);
+        for (a, b) in [(10, 3), (20, 20), (7, 1)] {
+            assert_float_eq(
+                super::generalized_factorial(a, b).ln(),
+                super::ln_generalized_factorial(a, b),
+            );
+        }
+    }
+
+    #[test]
+    fn gram_poly_is_one_at_window_end() {
+        for m in 1..6 {
+            for k in 0..=2 * m {
+                assert_float_eq(super::gram_poly(m, m, k, 0), 1.0);
+            }
+        }
+    }
+
+    #[test]
+    fn ln_factorial_fallback_matches_statrs() {
+        for n in 0..=170 {
+            assert_eq!(super::ln_factorial_fallback(n), super::ln_factorial(n));
+        }
+        for n in (171..5000).step_by(7) {
+            let expected = super::ln_factorial(n);
+            assert_relative_eq!(
+                super::ln_factorial_fallback(n),
+                expected,
+                max_relative = 1e-14
+            );
+        }
+    }
+
+    #[test]
+    fn weight_table_factors_match_ln_generalized_factorial() {
+        for (two_m, n) in [(4, 2), (9, 5), (200, 12)] {
+            let table = super::WeightTable::new(two_m, n);
+            for (k, factor) in table.factors.iter().enumerate() {
+                let k = k as i64;
+                let direct = (2 * k + 1) as f64
+                    * (super::ln_generalized_factorial(two_m, k)
+                        - super::ln_generalized_factorial(two_m + k + 1, k + 1))
+                    .exp();
+                assert_relative_eq!(*factor, direct, max_relative = 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn weight_table_shared_across_points_matches_weights() {
+        let table = super::WeightTable::new(8, 4);
+        for t in -4..=4 {
+            for i in -4..=4 {
+                let shared = table.weights(2 * i, 2 * t, &[0, 2]);
+                assert_float_eq(shared[0], super::weights(i, 4, 4, t, 0));
+                assert_float_eq(shared[1], super::weights(i, 4, 4, t, 2));
+            }
+        }
+    }
 }
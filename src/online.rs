@@ -0,0 +1,246 @@
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use crate::edge::EdgeMode;
+use crate::fallback::FallbackPolicy;
+use crate::filter::Filter;
+use crate::precomputed::{self, PrecomputedFilter};
+
+/// Applies a `Filter` to samples arriving one at a time.
+///
+/// Once a full window of samples has been pushed, each `push` returns the
+/// smoothed value of the sample `radius_right` steps back, so the latency is
+/// `radius_right` samples. The outputs match `Filter::smooth` on the whole
+/// stream: the first `radius_left` outputs are available from `leading_edge`
+/// once the window first fills, and `flush` returns the last `radius_right`.
+/// Edges are always handled as `EdgeMode::Interp`, and streams shorter than
+/// the window with `FallbackPolicy::Shrink`, whatever the filter's settings.
+pub struct OnlineFilter {
+    precomputed: PrecomputedFilter,
+    /// First-derivative weights at the newest sample, for `push_derivative`.
+    slope: Vec<f64>,
+    buffer: VecDeque<f64>,
+    leading_edge: Vec<f64>,
+}
+
+/// Applies a kernel to the samples in a ring buffer of the same length,
+/// summing exactly as `Filter::smooth` does.
+fn dot(kernel: &[f64], buffer: &mut VecDeque<f64>) -> f64 {
+    precomputed::dot(kernel, buffer.make_contiguous())
+}
+
+impl OnlineFilter {
+    pub fn new(filter: &Filter) -> Self {
+        let filter = filter
+            .with_options(filter.delta(), EdgeMode::Interp)
+            .with_fallback_policy(FallbackPolicy::Shrink);
+        let precomputed = filter.precompute();
+        let len = precomputed.kernel.len();
+        let slope = filter.newest_slope_coefficients();
+        OnlineFilter {
+            precomputed,
+            slope,
+            buffer: VecDeque::with_capacity(len),
+            leading_edge: Vec::new(),
+        }
+    }
+
+    /// Adds a sample, returning the smoothed value at the center of the
+    /// window, or `None` while the window is still filling.
+    pub fn push(&mut self, sample: f64) -> Option<f64> {
+        self.add(sample)
+            .then(|| dot(&self.precomputed.kernel, &mut self.buffer))
+    }
+
+    /// Adds a sample like `push`, but returns the slope of the fit at that
+    /// newest sample: a causal first-derivative estimate with no latency
+    /// once the window has filled, and `None` before then.
+    ///
+    /// The slope is per unit of the filter's `delta` (set with
+    /// `FilterBuilder::delta`), whatever its derivative order. Evaluating
+    /// the fit at the end of the window rather than its center avoids the
+    /// `radius_right` samples of delay, at the cost of more noise; it is
+    /// exact for polynomials up to the filter's degree, and zero for
+    /// degree 0.
+    pub fn push_derivative(&mut self, sample: f64) -> Option<f64> {
+        self.add(sample).then(|| dot(&self.slope, &mut self.buffer))
+    }
+
+    /// Adds a sample to the window, returning whether the window is full.
+    fn add(&mut self, sample: f64) -> bool {
+        let len = self.precomputed.kernel.len();
+        if self.buffer.len() == len {
+            self.buffer.pop_front();
+        }
+        self.buffer.push_back(sample);
+        if self.buffer.len() < len {
+            return false;
+        }
+        if self.leading_edge.is_empty() {
+            self.leading_edge = self
+                .precomputed
+                .head
+                .iter()
+                .map(|row| dot(row, &mut self.buffer))
+                .collect();
+        }
+        true
+    }
+
+    /// The smoothed values of the first `radius_left` samples, which `push` does
+    /// not return. Empty until the window has filled.
+    pub fn leading_edge(&self) -> &[f64] {
+        &self.leading_edge
+    }
+
+    /// Ends the stream, returning the smoothed values not yet emitted and
+    /// resetting the filter for a new stream.
+    ///
+    /// Normally these are the last `radius_right` points, evaluated with the edge
+    /// weights. If the stream was shorter than the window, nothing has been
+    /// emitted and every point is returned, smoothed as `Filter::smooth`
+    /// would with its reduced radius.
+    pub fn flush(&mut self) -> Vec<f64> {
+        let mut buffer = core::mem::take(&mut self.buffer);
+        self.leading_edge.clear();
+        if buffer.len() < self.precomputed.kernel.len() {
+            let data: Vec<f64> = buffer.into_iter().collect();
+            return self.precomputed.filter.smooth(&data);
+        }
+        self.precomputed
+            .tail
+            .iter()
+            .map(|row| dot(row, &mut buffer))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use approx::assert_relative_eq;
+
+    use super::OnlineFilter;
+    use crate::Filter;
+
+    fn assert_all_float_eq(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, b) in actual.iter().zip(expected) {
+            assert_relative_eq!(a, b, epsilon = 1e-10);
+        }
+    }
+
+    fn stream(online: &mut OnlineFilter, data: &[f64]) -> Vec<f64> {
+        let centered: Vec<f64> = data.iter().filter_map(|&x| online.push(x)).collect();
+        let mut output = online.leading_edge().to_vec();
+        output.extend(centered);
+        output.extend(online.flush());
+        output
+    }
+
+    #[test]
+    fn push_returns_none_during_warm_up() {
+        let mut online = OnlineFilter::new(&Filter::new(2, 2, 0));
+        for x in 0..4 {
+            assert_eq!(online.push(x as f64), None);
+        }
+        assert_relative_eq!(online.push(4.0).unwrap(), 2.0, epsilon = 1e-10);
+        assert_eq!(online.leading_edge().len(), 2);
+    }
+
+    #[test]
+    fn push_derivative_tracks_slope_of_ramp() {
+        let dt = 0.25;
+        let filter = crate::FilterBuilder::new()
+            .radius(3)
+            .degree(2)
+            .delta(dt)
+            .build()
+            .unwrap();
+        let mut online = OnlineFilter::new(&filter);
+        let slopes: Vec<Option<f64>> = (0..20)
+            .map(|k| online.push_derivative(1.0 + 3.0 * k as f64 * dt))
+            .collect();
+        assert!(slopes[..6].iter().all(Option::is_none));
+        for slope in &slopes[6..] {
+            assert_relative_eq!(slope.unwrap(), 3.0, epsilon = 1e-10);
+        }
+        let mut constant = OnlineFilter::new(&Filter::new(2, 0, 0));
+        for k in 0..4 {
+            constant.push_derivative(k as f64);
+        }
+        assert_eq!(constant.push_derivative(4.0), Some(0.0));
+    }
+
+    #[test]
+    fn push_derivative_of_quadratic_with_even_window() {
+        let dt = 0.5;
+        let filter = crate::FilterBuilder::new()
+            .window_length(6)
+            .degree(2)
+            .delta(dt)
+            .build()
+            .unwrap();
+        let mut online = OnlineFilter::new(&filter);
+        for k in 0..15 {
+            let x = k as f64 * dt;
+            let slope = online.push_derivative(x * x - 2.0 * x);
+            match slope {
+                None => assert!(k < 5),
+                Some(slope) => assert_relative_eq!(slope, 2.0 * x - 2.0, epsilon = 1e-10),
+            }
+        }
+    }
+
+    #[test]
+    fn online_ramp_matches_batch() {
+        let filter = Filter::new(3, 2, 0);
+        let data: Vec<f64> = (0..30).map(|x| x as f64 * 0.5).collect();
+        let mut online = OnlineFilter::new(&filter);
+        assert_all_float_eq(&stream(&mut online, &data), &filter.smooth(&data));
+    }
+
+    #[test]
+    fn online_nonlinear_derivative_matches_batch() {
+        let filter = Filter::new(2, 3, 1);
+        let data: Vec<f64> = (0..20).map(|x| (x as f64 * 0.7).sin()).collect();
+        let mut online = OnlineFilter::new(&filter);
+        assert_eq!(stream(&mut online, &data), filter.smooth(&data));
+    }
+
+    #[test]
+    fn online_ignores_edge_mode_and_fallback_policy() {
+        let filter = crate::FilterBuilder::new()
+            .radius(3)
+            .edge_mode(crate::EdgeMode::Constant(2.0))
+            .fallback_policy(crate::FallbackPolicy::Error)
+            .build()
+            .unwrap();
+        for len in [5, 30] {
+            let data: Vec<f64> = (0..len).map(|x| (x as f64 * 0.9).cos()).collect();
+            let mut online = OnlineFilter::new(&filter);
+            assert_eq!(
+                stream(&mut online, &data),
+                Filter::new(3, 2, 0).smooth(&data)
+            );
+        }
+    }
+
+    #[test]
+    fn online_short_stream_flushes_everything() {
+        let filter = Filter::new(20, 2, 0);
+        let data = [1.0, -2.0, 3.0, -4.0, 5.0];
+        let mut online = OnlineFilter::new(&filter);
+        assert_all_float_eq(&stream(&mut online, &data), &filter.smooth(&data));
+    }
+
+    #[test]
+    fn flush_resets_for_a_new_stream() {
+        let filter = Filter::new(1, 1, 0);
+        let mut online = OnlineFilter::new(&filter);
+        stream(&mut online, &[5.0, 1.0, 4.0, 2.0]);
+        let data = [1.0, 2.0, 3.0, 4.0];
+        assert_all_float_eq(&stream(&mut online, &data), &filter.smooth(&data));
+    }
+}
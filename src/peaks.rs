@@ -0,0 +1,102 @@
+//! Peak detection on top of the smoothed derivatives.
+
+use alloc::vec::Vec;
+
+// Test builds link std, whose inherent float methods make this unused.
+#[cfg(not(any(feature = "std", test)))]
+use num_traits::Float;
+
+use crate::Filter;
+
+/// The local maxima of `data`, found as the downward zero crossings of its
+/// first derivative under a `Filter::new(radius, degree, 0)` fit.
+///
+/// At each crossing the fit nearer to zero slope is followed to its vertex,
+/// `k - f'(k) / f''(k)`, where the second derivative must be negative. Each
+/// peak is reported as the nearest sample to the vertex together with the
+/// vertex itself, a fractional position clamped to the ends of `data`, so
+/// the index need not be one of the two samples the crossing lies between.
+///
+/// On a noisy baseline this includes the small ripples the smoothing lets
+/// through; [`peaks_above`] drops those below a height.
+///
+/// # Panics
+///
+/// If `degree < 2` or `degree >= 2 * radius + 1`.
+pub fn peaks(data: &[f64], radius: usize, degree: u64) -> Vec<(usize, f64)> {
+    peaks_above(data, radius, degree, f64::NEG_INFINITY)
+}
+
+/// [`peaks`] whose smoothed value at the crossing is at least `min_height`.
+///
+/// # Panics
+///
+/// If `degree < 2` or `degree >= 2 * radius + 1`.
+pub fn peaks_above(data: &[f64], radius: usize, degree: u64, min_height: f64) -> Vec<(usize, f64)> {
+    assert!(
+        degree >= 2,
+        "peaks needs a second derivative, so degree >= 2"
+    );
+    let derivatives = Filter::new(radius, degree, 0).smooth_derivatives(data, &[0, 1, 2]);
+    let (value, slope, curvature) = (&derivatives[0], &derivatives[1], &derivatives[2]);
+    let mut found: Vec<(usize, f64)> = Vec::new();
+    for k in 1..data.len() {
+        if !(slope[k - 1] > 0.0 && slope[k] <= 0.0) {
+            continue;
+        }
+        let j = if slope[k - 1].abs() < slope[k].abs() {
+            k - 1
+        } else {
+            k
+        };
+        if !(curvature[j] < 0.0 && value[j] >= min_height) {
+            continue;
+        }
+        let vertex = (j as f64 - slope[j] / curvature[j]).clamp(0.0, (data.len() - 1) as f64);
+        let index = vertex.round() as usize;
+        if found.last().map(|&(i, _)| i) != Some(index) {
+            found.push((index, vertex));
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::{peaks, peaks_above};
+
+    #[test]
+    fn finds_two_gaussians_in_noise() {
+        let mut state: u64 = 12345;
+        let mut noise = || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((state >> 11) as f64 / (1u64 << 53) as f64 - 0.5) * 0.05
+        };
+        let gaussian = |x: f64, center: f64| (-(x - center).powi(2) / (2.0 * 8.0 * 8.0)).exp();
+        let data: Vec<f64> = (0..200)
+            .map(|k| gaussian(k as f64, 60.3) + 0.7 * gaussian(k as f64, 139.8) + noise())
+            .collect();
+        let found = peaks_above(&data, 8, 2, 0.3);
+        assert_eq!(found.len(), 2, "{:?}", found);
+        assert!(found[0].0.abs_diff(60) <= 1, "{:?}", found);
+        assert!((found[0].1 - 60.3).abs() < 1.0, "{:?}", found);
+        assert!(found[1].0.abs_diff(140) <= 1, "{:?}", found);
+        assert!((found[1].1 - 139.8).abs() < 1.0, "{:?}", found);
+        assert!(peaks(&data, 8, 2).len() > 2);
+    }
+
+    #[test]
+    fn finds_vertex_of_parabola() {
+        let data: Vec<f64> = (0..21).map(|k| -(k as f64 - 12.4).powi(2)).collect();
+        let found = peaks(&data, 3, 2);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, 12);
+        assert!((found[0].1 - 12.4).abs() < 1e-10, "{:?}", found);
+        let valley: Vec<f64> = data.iter().map(|x| -x).collect();
+        assert!(peaks(&valley, 3, 2).is_empty());
+    }
+}
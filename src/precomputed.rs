@@ -0,0 +1,139 @@
+use alloc::vec::Vec;
+
+use num_traits::Float;
+
+use crate::edge::EdgeMode;
+use crate::filter::Filter;
+use crate::math::CompensatedSum;
+
+/// A `Filter` with its interior kernel and edge weight rows computed up front.
+///
+/// Constructing one evaluates `window_length^2` weights, costing
+/// O(window_length^2 * degree), after which `smooth` is O(n * window_length) and never calls
+/// back into the weight computation. Inputs shorter than the window are the
+/// exception: they fall back to `Filter::smooth`, which shrinks the radius.
+/// So do all inputs when the filter has an edge mode other than `Interp`.
+pub struct PrecomputedFilter {
+    pub(crate) filter: Filter,
+    /// Weights for the interior points (t = 0), from i = -radius_left to
+    /// radius_right.
+    pub(crate) kernel: Vec<f64>,
+    /// Weights for the first `radius_left` points (t = -radius_left..=-1).
+    pub(crate) head: Vec<Vec<f64>>,
+    /// Weights for the last `radius_right` points (t = 1..=radius_right).
+    pub(crate) tail: Vec<Vec<f64>>,
+}
+
+/// Applies a kernel to a window of the same length, with compensated
+/// summation. The weights are cast to `T` as they are applied.
+pub(crate) fn dot<T: Float>(kernel: &[f64], window: &[T]) -> T {
+    let mut sum = CompensatedSum::default();
+    for (&weight, &value) in kernel.iter().zip(window) {
+        sum.add(T::from(weight).unwrap() * value);
+    }
+    sum.value()
+}
+
+impl PrecomputedFilter {
+    pub fn smooth<T: Float>(&self, data: &[T]) -> Vec<T> {
+        let mut smoothed = Vec::with_capacity(data.len());
+        self.smooth_streaming_cb(data, |_, value| smoothed.push(value));
+        smoothed
+    }
+
+    /// Smooths `data`, calling `cb(index, value)` as soon as each output point
+    /// is computed. Points are emitted in index order and match `smooth`.
+    pub fn smooth_streaming_cb<T: Float>(&self, data: &[T], mut cb: impl FnMut(usize, T)) {
+        let len = self.kernel.len();
+        if data.len() < len || self.filter.edge_mode() != EdgeMode::Interp {
+            return self.filter.smooth_streaming_cb(data, cb);
+        }
+        let mut index = 0;
+        let mut emit = |value| {
+            cb(index, value);
+            index += 1;
+        };
+        for row in &self.head {
+            emit(dot(row, &data[..len]));
+        }
+        #[cfg(feature = "simd")]
+        crate::simd::convolve(&self.kernel, data, &mut emit);
+        #[cfg(not(feature = "simd"))]
+        for window in data.windows(len) {
+            emit(dot(&self.kernel, window));
+        }
+        for row in &self.tail {
+            emit(dot(row, &data[data.len() - len..]));
+        }
+    }
+
+    /// The weights for evaluating the fit at offset `t` from the window's
+    /// reference point, as in `Filter::kernel`.
+    pub(crate) fn row(&self, t: i64) -> &[f64] {
+        match t {
+            0 => &self.kernel,
+            t if t < 0 => &self.head[(t + self.head.len() as i64) as usize],
+            t => &self.tail[t as usize - 1],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use approx::assert_relative_eq;
+
+    use crate::Filter;
+
+    fn assert_float_eq(a: f64, b: f64) {
+        assert_relative_eq!(a, b, epsilon = 1e-10);
+    }
+
+    #[test]
+    fn precomputed_rows_have_window_length() {
+        let precomputed = Filter::new(3, 2, 0).precompute();
+        assert_eq!(precomputed.kernel.len(), 7);
+        assert_eq!(precomputed.head.len(), 3);
+        assert_eq!(precomputed.tail.len(), 3);
+        assert!(precomputed
+            .head
+            .iter()
+            .chain(&precomputed.tail)
+            .all(|row| row.len() == 7));
+    }
+
+    #[test]
+    fn precomputed_smooth_matches_filter() {
+        let filter = Filter::new(3, 2, 1);
+        let precomputed = filter.precompute();
+        for len in [0, 1, 2, 5, 7, 50] {
+            let data: Vec<f64> = (0..len).map(|x| (x as f64 * 0.3).sin() * 4.0).collect();
+            for (actual, expected) in precomputed.smooth(&data).iter().zip(filter.smooth(&data)) {
+                assert_float_eq(*actual, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn precomputed_5pt_quadratic_on_7pts_nonlinear() {
+        let precomputed = Filter::new(2, 2, 0).precompute();
+        let smoothed = precomputed.smooth(&[1.0, -2.0, 3.0, -4.0, 5.0, -6.0, 7.0]);
+        assert_float_eq(smoothed[0], 1.1142857142857143);
+        assert_float_eq(smoothed[3], 1.4857142857142858);
+        assert_float_eq(smoothed[6], 5.057142857142857);
+    }
+
+    #[test]
+    fn smooth_allocates_exactly_once() {
+        let filter = Filter::new(3, 2, 0);
+        let precomputed = filter.precompute();
+        for len in [0, 1, 5, 7, 1000] {
+            let data: Vec<f64> = (0..len).map(|x| x as f64).collect();
+            let smoothed = precomputed.smooth(&data);
+            assert_eq!(smoothed.capacity(), len);
+            let smoothed = filter.smooth(&data);
+            assert_eq!(smoothed.capacity(), len);
+        }
+    }
+}
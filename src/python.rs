@@ -0,0 +1,106 @@
+//! Python bindings mirroring `scipy.signal.savgol_filter` and
+//! `scipy.signal.savgol_coeffs`, built as the `savgol` extension module.
+
+use numpy::{IntoPyArray, PyArray1, PyReadonlyArray1};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::builder::FilterBuilder;
+use crate::edge::EdgeMode;
+
+fn edge_mode(mode: &str, cval: f64) -> PyResult<EdgeMode> {
+    match mode {
+        "interp" => Ok(EdgeMode::Interp),
+        "mirror" => Ok(EdgeMode::Mirror),
+        "nearest" => Ok(EdgeMode::Nearest),
+        "constant" => Ok(EdgeMode::Constant(cval)),
+        "wrap" => Ok(EdgeMode::Wrap),
+        _ => Err(PyValueError::new_err(
+            "mode must be 'mirror', 'constant', 'nearest', 'wrap' or 'interp'.",
+        )),
+    }
+}
+
+/// Applies a Savitzky-Golay filter to a 1-D array, like
+/// `scipy.signal.savgol_filter`.
+#[pyfunction]
+#[pyo3(signature = (x, window_length, polyorder, deriv=0, delta=1.0, mode="interp", cval=0.0))]
+#[allow(clippy::too_many_arguments)]
+fn savgol_filter<'py>(
+    py: Python<'py>,
+    x: PyReadonlyArray1<'py, f64>,
+    window_length: usize,
+    polyorder: u64,
+    deriv: u64,
+    delta: f64,
+    mode: &str,
+    cval: f64,
+) -> PyResult<Bound<'py, PyArray1<f64>>> {
+    let x = x.as_array().to_vec();
+    let edge_mode = edge_mode(mode, cval)?;
+    if polyorder as usize >= window_length {
+        return Err(PyValueError::new_err(
+            "polyorder must be less than window_length.",
+        ));
+    }
+    if edge_mode == EdgeMode::Interp && window_length > x.len() {
+        return Err(PyValueError::new_err(
+            "If mode is 'interp', window_length must be less than or equal to the size of x.",
+        ));
+    }
+    if deriv > polyorder {
+        // SciPy's coefficients are all zero in this case.
+        return Ok(vec![0.0; x.len()].into_pyarray(py));
+    }
+    let filter = FilterBuilder::new()
+        .window_length(window_length)
+        .degree(polyorder)
+        .derivative(deriv)
+        .delta(delta)
+        .edge_mode(edge_mode)
+        .build()
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok(filter.smooth(&x).into_pyarray(py))
+}
+
+/// Coefficients of a 1-D Savitzky-Golay FIR filter, like
+/// `scipy.signal.savgol_coeffs`. Only odd window lengths are supported.
+#[pyfunction]
+#[pyo3(signature = (window_length, polyorder, deriv=0, delta=1.0, pos=None, r#use="conv"))]
+fn savgol_coeffs<'py>(
+    py: Python<'py>,
+    window_length: usize,
+    polyorder: usize,
+    deriv: usize,
+    delta: f64,
+    pos: Option<i64>,
+    r#use: &str,
+) -> PyResult<Bound<'py, PyArray1<f64>>> {
+    if window_length.is_multiple_of(2) {
+        return Err(PyValueError::new_err("window_length must be odd."));
+    }
+    if polyorder >= window_length {
+        return Err(PyValueError::new_err(
+            "polyorder must be less than window_length.",
+        ));
+    }
+    if pos.is_some_and(|pos| !(0..window_length as i64).contains(&pos)) {
+        return Err(PyValueError::new_err(
+            "pos must be nonnegative and less than window_length.",
+        ));
+    }
+    let mut coeffs = crate::scipy::savgol_coeffs(window_length, polyorder, deriv, delta, pos);
+    match r#use {
+        "conv" => {}
+        "dot" => coeffs.reverse(),
+        _ => return Err(PyValueError::new_err("`use` must be 'conv' or 'dot'")),
+    }
+    Ok(coeffs.into_pyarray(py))
+}
+
+#[pymodule]
+fn savgol(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(savgol_filter, m)?)?;
+    m.add_function(wrap_pyfunction!(savgol_coeffs, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,117 @@
+//! Functions mirroring `scipy.signal`, to ease porting code from Python.
+
+use alloc::vec::Vec;
+
+// Test builds link std, whose inherent float methods make this unused.
+#[cfg(not(any(feature = "std", test)))]
+use num_traits::Float;
+
+use crate::math;
+
+/// Coefficients of a 1-D Savitzky–Golay FIR filter, like
+/// `scipy.signal.savgol_coeffs(window_length, polyorder, deriv, delta, pos)`
+/// with SciPy's default `use="conv"`.
+///
+/// As in SciPy, the coefficients are in convolution order, i.e. reversed
+/// relative to the window: the first coefficient multiplies the last sample.
+/// Reverse them to get the `use="dot"` order, which is the order of
+/// `Filter::coefficients`.
+///
+/// `pos` is the index within the window at which the fit is evaluated and
+/// defaults to the center, `window_length / 2`. In this crate's terms, with
+/// `radius = (window_length - 1) / 2`, sample `j` of the window is at
+/// `i = j - radius` and the evaluation point is at `t = pos - radius`.
+///
+/// Derivatives are divided by `delta.powi(deriv)`, the sample spacing, and
+/// are all zero when `deriv > polyorder`.
+///
+/// # Panics
+///
+/// If `window_length` is even, `polyorder >= window_length`, or `pos` is
+/// outside `0..window_length`.
+pub fn savgol_coeffs(
+    window_length: usize,
+    polyorder: usize,
+    deriv: usize,
+    delta: f64,
+    pos: Option<i64>,
+) -> Vec<f64> {
+    assert!(window_length % 2 == 1, "window_length must be odd");
+    assert!(
+        polyorder < window_length,
+        "polyorder must be less than window_length"
+    );
+    let radius = (window_length / 2) as i64;
+    let pos = pos.unwrap_or(radius);
+    assert!(
+        (0..window_length as i64).contains(&pos),
+        "pos must be nonnegative and less than window_length"
+    );
+    let scale = delta.powi(deriv as i32);
+    (-radius..=radius)
+        .rev()
+        .map(|i| math::weights(i, radius, polyorder as i64, pos - radius, deriv as i64) / scale)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::savgol_coeffs;
+
+    fn assert_all_float_eq(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, b) in actual.iter().zip(expected) {
+            assert_relative_eq!(a, b, epsilon = 1e-8);
+        }
+    }
+
+    #[test]
+    fn savgol_coeffs_5_2() {
+        // scipy.signal.savgol_coeffs(5, 2)
+        assert_all_float_eq(
+            &savgol_coeffs(5, 2, 0, 1.0, None),
+            &[-0.08571429, 0.34285714, 0.48571429, 0.34285714, -0.08571429],
+        );
+    }
+
+    #[test]
+    fn savgol_coeffs_5_2_deriv_1() {
+        // scipy.signal.savgol_coeffs(5, 2, deriv=1)
+        assert_all_float_eq(
+            &savgol_coeffs(5, 2, 1, 1.0, None),
+            &[0.2, 0.1, 0.0, -0.1, -0.2],
+        );
+    }
+
+    #[test]
+    fn savgol_coeffs_5_2_pos_3() {
+        // scipy.signal.savgol_coeffs(5, 2, pos=3)
+        assert_all_float_eq(
+            &savgol_coeffs(5, 2, 0, 1.0, Some(3)),
+            &[0.25714286, 0.37142857, 0.34285714, 0.17142857, -0.14285714],
+        );
+    }
+
+    #[test]
+    fn savgol_coeffs_delta_scales_derivative() {
+        // scipy.signal.savgol_coeffs(5, 2, deriv=1, delta=0.5)
+        assert_all_float_eq(
+            &savgol_coeffs(5, 2, 1, 0.5, None),
+            &[0.4, 0.2, 0.0, -0.2, -0.4],
+        );
+    }
+
+    #[test]
+    fn savgol_coeffs_deriv_above_polyorder_is_zero() {
+        // scipy.signal.savgol_coeffs(5, 1, deriv=2)
+        assert_all_float_eq(&savgol_coeffs(5, 1, 2, 1.0, None), &[0.0; 5]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn savgol_coeffs_rejects_even_window() {
+        savgol_coeffs(4, 2, 0, 1.0, None);
+    }
+}
@@ -0,0 +1,82 @@
+use polars::prelude::*;
+
+use crate::filter::Filter;
+
+impl Filter {
+    /// Smooths a `Float64` or `Float32` Series, returning a new Series of the
+    /// same name and type.
+    ///
+    /// Nulls are treated as missing samples, as NaN is by `smooth_nan`, so
+    /// they are filled in from the fit through their neighbours. An output
+    /// is NaN when its window has too few samples left to fit the degree.
+    pub fn smooth_series(&self, s: &Series) -> PolarsResult<Series> {
+        match s.dtype() {
+            DataType::Float64 => {
+                let data: Vec<f64> = s.f64()?.iter().map(|v| v.unwrap_or(f64::NAN)).collect();
+                Ok(Series::new(s.name().clone(), self.smooth_nan(&data)))
+            }
+            DataType::Float32 => {
+                let data: Vec<f64> = s
+                    .f32()?
+                    .iter()
+                    .map(|v| v.map_or(f64::NAN, f64::from))
+                    .collect();
+                let smoothed: Vec<f32> = self.smooth_nan(&data).iter().map(|&v| v as f32).collect();
+                Ok(Series::new(s.name().clone(), smoothed))
+            }
+            dtype => polars_bail!(InvalidOperation: "cannot smooth a Series of type {}", dtype),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use polars::prelude::*;
+
+    use crate::Filter;
+
+    #[test]
+    fn smooth_series_float64_matches_smooth() {
+        let filter = Filter::new(2, 2, 0);
+        let data = vec![1.0, -2.0, 3.0, -4.0, 5.0, -6.0, 7.0];
+        let smoothed = filter
+            .smooth_series(&Series::new("y".into(), &data))
+            .unwrap();
+        assert_eq!(smoothed.name().as_str(), "y");
+        assert_eq!(smoothed.dtype(), &DataType::Float64);
+        let values: Vec<f64> = smoothed.f64().unwrap().into_no_null_iter().collect();
+        for (actual, expected) in values.iter().zip(filter.smooth(&data)) {
+            assert_relative_eq!(*actual, expected, epsilon = 1e-10);
+        }
+    }
+
+    #[test]
+    fn smooth_series_treats_nulls_as_missing() {
+        let filter = Filter::new(2, 2, 0);
+        let column: Vec<Option<f64>> = (0..9)
+            .map(|x| if x == 4 { None } else { Some((x * x) as f64) })
+            .collect();
+        let smoothed = filter
+            .smooth_series(&Series::new("y".into(), &column))
+            .unwrap();
+        let values: Vec<f64> = smoothed.f64().unwrap().into_no_null_iter().collect();
+        for (x, value) in values.iter().enumerate() {
+            assert_relative_eq!(*value, (x * x) as f64, epsilon = 1e-10);
+        }
+
+        let sparse: Vec<Option<f32>> = vec![Some(1.0), None, None, None, None, Some(2.0)];
+        let smoothed = filter
+            .smooth_series(&Series::new("z".into(), &sparse))
+            .unwrap();
+        assert_eq!(smoothed.dtype(), &DataType::Float32);
+        assert!(smoothed.f32().unwrap().into_no_null_iter().all(f32::is_nan));
+    }
+
+    #[test]
+    fn smooth_series_rejects_integers() {
+        let filter = Filter::new(2, 2, 0);
+        let series = Series::new("n".into(), &[1i64, 2, 3]);
+        assert!(filter.smooth_series(&series).is_err());
+    }
+}
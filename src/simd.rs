@@ -0,0 +1,95 @@
+use num_traits::Float;
+use wide::{f64x4, CmpGe};
+
+use crate::precomputed::dot;
+
+const LANES: usize = 4;
+
+/// Applies `kernel` to every full window of `data`, calling `emit` with the
+/// outputs in order, four windows at a time.
+///
+/// Each lane repeats the scalar `dot` step for step, with the same products
+/// and the same Neumaier compensation, so for `f64` data the output is
+/// bit-identical to the scalar path. Other types are accumulated in `f64`
+/// and rounded once. The last few windows, fewer than a full vector, are
+/// done with the scalar `dot`.
+pub(crate) fn convolve<T: Float>(kernel: &[f64], data: &[T], mut emit: impl FnMut(T)) {
+    let len = kernel.len();
+    let vectorized = (data.len() + 1 - len) / LANES * LANES;
+    for start in (0..vectorized).step_by(LANES) {
+        let mut sum = f64x4::splat(0.0);
+        let mut compensation = f64x4::splat(0.0);
+        for (j, &weight) in kernel.iter().enumerate() {
+            let samples: [f64; LANES] =
+                core::array::from_fn(|lane| data[start + lane + j].to_f64().unwrap());
+            let value = f64x4::splat(weight) * f64x4::from(samples);
+            let total = sum + value;
+            compensation += sum
+                .abs()
+                .cmp_ge(value.abs())
+                .blend((sum - total) + value, (value - total) + sum);
+            sum = total;
+        }
+        for output in (sum + compensation).to_array() {
+            emit(T::from(output).unwrap());
+        }
+    }
+    for window in data[vectorized..].windows(len) {
+        emit(dot(kernel, window));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::convolve;
+    use crate::precomputed::dot;
+    use crate::Filter;
+
+    fn random(len: usize) -> Vec<f64> {
+        let mut state: u64 = 0x2545f4914f6cdd1d;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                (state >> 11) as f64 / (1u64 << 53) as f64 * 200.0 - 100.0
+            })
+            .collect()
+    }
+
+    #[test]
+    fn matches_scalar_on_random_data() {
+        for (radius, degree) in [(2, 2), (5, 3), (12, 6)] {
+            let kernel = Filter::new(radius, degree, 0).coefficients();
+            // Lengths leaving every remainder of scalar windows.
+            for len in [kernel.len(), 1000, 1001, 1002, 1003] {
+                let data = random(len);
+                let scalar: Vec<f64> = data
+                    .windows(kernel.len())
+                    .map(|w| dot(&kernel, w))
+                    .collect();
+                let mut vectorized = Vec::new();
+                convolve(&kernel, &data, |value| vectorized.push(value));
+                assert_eq!(vectorized, scalar);
+            }
+        }
+    }
+
+    #[test]
+    fn f32_matches_scalar_within_tolerance() {
+        let kernel = Filter::new(4, 2, 1).coefficients();
+        let data: Vec<f32> = random(500).into_iter().map(|x| x as f32).collect();
+        let scalar: Vec<f32> = data
+            .windows(kernel.len())
+            .map(|w| dot(&kernel, w))
+            .collect();
+        let mut vectorized = Vec::new();
+        convolve(&kernel, &data, |value| vectorized.push(value));
+        assert_eq!(vectorized.len(), scalar.len());
+        for (a, b) in vectorized.iter().zip(&scalar) {
+            assert_relative_eq!(a, b, epsilon = 1e-3);
+        }
+    }
+}
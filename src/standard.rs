@@ -0,0 +1,182 @@
+//! The classic Savitzky–Golay kernels as exact rational constants.
+
+/// 5-point quadratic/cubic smoothing.
+const SMOOTH_5: [f64; 5] = [
+    -3.0 / 35.0,
+    12.0 / 35.0,
+    17.0 / 35.0,
+    12.0 / 35.0,
+    -3.0 / 35.0,
+];
+const SMOOTH_7: [f64; 7] = [
+    -2.0 / 21.0,
+    3.0 / 21.0,
+    6.0 / 21.0,
+    7.0 / 21.0,
+    6.0 / 21.0,
+    3.0 / 21.0,
+    -2.0 / 21.0,
+];
+const SMOOTH_9: [f64; 9] = [
+    -21.0 / 231.0,
+    14.0 / 231.0,
+    39.0 / 231.0,
+    54.0 / 231.0,
+    59.0 / 231.0,
+    54.0 / 231.0,
+    39.0 / 231.0,
+    14.0 / 231.0,
+    -21.0 / 231.0,
+];
+const SMOOTH_11: [f64; 11] = [
+    -36.0 / 429.0,
+    9.0 / 429.0,
+    44.0 / 429.0,
+    69.0 / 429.0,
+    84.0 / 429.0,
+    89.0 / 429.0,
+    84.0 / 429.0,
+    69.0 / 429.0,
+    44.0 / 429.0,
+    9.0 / 429.0,
+    -36.0 / 429.0,
+];
+/// 7-point quartic/quintic smoothing.
+const SMOOTH_QUARTIC_7: [f64; 7] = [
+    5.0 / 231.0,
+    -30.0 / 231.0,
+    75.0 / 231.0,
+    131.0 / 231.0,
+    75.0 / 231.0,
+    -30.0 / 231.0,
+    5.0 / 231.0,
+];
+const SMOOTH_QUARTIC_9: [f64; 9] = [
+    15.0 / 429.0,
+    -55.0 / 429.0,
+    30.0 / 429.0,
+    135.0 / 429.0,
+    179.0 / 429.0,
+    135.0 / 429.0,
+    30.0 / 429.0,
+    -55.0 / 429.0,
+    15.0 / 429.0,
+];
+/// 5-point linear/quadratic first derivative.
+const FIRST_5: [f64; 5] = [-2.0 / 10.0, -1.0 / 10.0, 0.0, 1.0 / 10.0, 2.0 / 10.0];
+const FIRST_7: [f64; 7] = [
+    -3.0 / 28.0,
+    -2.0 / 28.0,
+    -1.0 / 28.0,
+    0.0,
+    1.0 / 28.0,
+    2.0 / 28.0,
+    3.0 / 28.0,
+];
+const FIRST_9: [f64; 9] = [
+    -4.0 / 60.0,
+    -3.0 / 60.0,
+    -2.0 / 60.0,
+    -1.0 / 60.0,
+    0.0,
+    1.0 / 60.0,
+    2.0 / 60.0,
+    3.0 / 60.0,
+    4.0 / 60.0,
+];
+/// 5-point quadratic/cubic second derivative.
+const SECOND_5: [f64; 5] = [2.0 / 7.0, -1.0 / 7.0, -2.0 / 7.0, -1.0 / 7.0, 2.0 / 7.0];
+const SECOND_7: [f64; 7] = [
+    5.0 / 42.0,
+    0.0,
+    -3.0 / 42.0,
+    -4.0 / 42.0,
+    -3.0 / 42.0,
+    0.0,
+    5.0 / 42.0,
+];
+const SECOND_9: [f64; 9] = [
+    28.0 / 462.0,
+    7.0 / 462.0,
+    -8.0 / 462.0,
+    -17.0 / 462.0,
+    -20.0 / 462.0,
+    -17.0 / 462.0,
+    -8.0 / 462.0,
+    7.0 / 462.0,
+    28.0 / 462.0,
+];
+
+/// The centered kernel of `Filter::new_window(window, degree, deriv)`, in the
+/// order of `Filter::coefficients`, from a built-in table of the classic
+/// kernels: smoothing of degree 2 or 3 over 5 to 11 points and of degree 4
+/// or 5 over 7 or 9 points, and first (degree 1 or 2) and second (degree 2
+/// or 3) derivatives over 5 to 9 points.
+///
+/// Each entry is the nearest `f64` to the exact rational coefficient.
+/// Returns `None` for anything not in the table.
+pub fn standard_coefficients(window: usize, degree: u64, deriv: u64) -> Option<&'static [f64]> {
+    let coefficients: &'static [f64] = match (window, degree, deriv) {
+        (5, 2 | 3, 0) => &SMOOTH_5,
+        (7, 2 | 3, 0) => &SMOOTH_7,
+        (9, 2 | 3, 0) => &SMOOTH_9,
+        (11, 2 | 3, 0) => &SMOOTH_11,
+        (7, 4 | 5, 0) => &SMOOTH_QUARTIC_7,
+        (9, 4 | 5, 0) => &SMOOTH_QUARTIC_9,
+        (5, 1 | 2, 1) => &FIRST_5,
+        (7, 1 | 2, 1) => &FIRST_7,
+        (9, 1 | 2, 1) => &FIRST_9,
+        (5, 2 | 3, 2) => &SECOND_5,
+        (7, 2 | 3, 2) => &SECOND_7,
+        (9, 2 | 3, 2) => &SECOND_9,
+        _ => return None,
+    };
+    Some(coefficients)
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::standard_coefficients;
+    use crate::Filter;
+
+    fn assert_matches_computed(window: usize, degree: u64, deriv: u64) {
+        let table = standard_coefficients(window, degree, deriv).unwrap();
+        let computed = Filter::new_window(window, degree, deriv).coefficients();
+        assert_eq!(table.len(), computed.len());
+        for (a, b) in table.iter().zip(&computed) {
+            assert_relative_eq!(a, b, epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn five_and_seven_point_kernels_match_computed() {
+        for (degree, deriv) in [(2, 0), (3, 0), (1, 1), (2, 1), (2, 2), (3, 2)] {
+            assert_matches_computed(5, degree, deriv);
+            assert_matches_computed(7, degree, deriv);
+        }
+        assert_matches_computed(7, 4, 0);
+        assert_matches_computed(7, 5, 0);
+    }
+
+    #[test]
+    fn every_tabulated_kernel_matches_computed() {
+        for window in 0..16 {
+            for degree in 0..8 {
+                for deriv in 0..=degree {
+                    if standard_coefficients(window, degree, deriv).is_some() {
+                        assert_matches_computed(window, degree, deriv);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn untabulated_is_none() {
+        assert_eq!(standard_coefficients(5, 2, 3), None);
+        assert_eq!(standard_coefficients(13, 2, 0), None);
+        assert_eq!(standard_coefficients(6, 2, 0), None);
+    }
+}
@@ -0,0 +1,50 @@
+//! WebAssembly bindings for JavaScript, built with `wasm-pack build wasm`.
+
+use alloc::vec::Vec;
+
+use wasm_bindgen::prelude::*;
+
+use crate::filter::Filter;
+use crate::precomputed::PrecomputedFilter;
+
+/// A `Filter` with its weights computed up front, so that repeated calls to
+/// `smooth` only convolve.
+#[wasm_bindgen]
+pub struct WasmFilter {
+    precomputed: PrecomputedFilter,
+}
+
+#[wasm_bindgen]
+impl WasmFilter {
+    /// Throws if the parameters are invalid, as `Filter::try_new` would fail.
+    #[wasm_bindgen(constructor)]
+    pub fn new(radius: usize, degree: u32, derivative: u32) -> Result<WasmFilter, JsError> {
+        let filter = Filter::try_new(radius, degree.into(), derivative.into())?;
+        Ok(WasmFilter {
+            precomputed: filter.precompute(),
+        })
+    }
+
+    /// Takes and returns a `Float64Array`. The input is copied into wasm
+    /// memory once and the output out of it once.
+    pub fn smooth(&self, data: &[f64]) -> Vec<f64> {
+        self.precomputed.smooth(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::WasmFilter;
+
+    #[test]
+    fn smooth_ramp_is_unchanged() {
+        let ramp: Vec<f64> = (0..10).map(|x| x as f64 * 0.5 - 1.0).collect();
+        let filter = WasmFilter::new(2, 2, 0).ok().unwrap();
+        for (actual, expected) in filter.smooth(&ramp).iter().zip(&ramp) {
+            assert!((actual - expected).abs() < 1e-10);
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn savgol(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_savgol"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn fixture(name: &str) -> String {
+    format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"))
+}
+
+#[test]
+fn smooths_csv_column_from_file() {
+    let output = savgol(&["--column", "1", &fixture("parabola.csv")], "");
+    assert!(output.status.success());
+    let expected = std::fs::read_to_string(fixture("parabola_smoothed.txt")).unwrap();
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+}
+
+#[test]
+fn applies_derivative_delta_and_edge_flags() {
+    let args = [
+        "--column",
+        "1",
+        "--deriv",
+        "1",
+        "--delta",
+        "0.5",
+        "--edge",
+        "nearest",
+        &fixture("parabola.csv"),
+    ];
+    let output = savgol(&args, "");
+    assert!(output.status.success());
+    let expected = std::fs::read_to_string(fixture("parabola_derivative.txt")).unwrap();
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+}
+
+#[test]
+fn reads_stdin_and_matches_library() {
+    let output = savgol(&["--radius", "1", "--degree", "1"], "3\n1\n\n4\n1\n5\n");
+    assert!(output.status.success());
+    let values: Vec<f64> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| line.parse().unwrap())
+        .collect();
+    let expected = savgol::Filter::new(1, 1, 0).smooth(&[3.0, 1.0, 4.0, 1.0, 5.0]);
+    assert_eq!(values, expected);
+}
+
+#[test]
+fn rejects_unknown_option() {
+    let output = savgol(&["--bogus"], "");
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("unknown option: --bogus"));
+}
@@ -0,0 +1,4 @@
+//! The `savgol` JavaScript package. `wasm-pack build wasm` links the
+//! bindings of the `wasm` feature into a cdylib.
+
+pub use savgol::*;